@@ -1,11 +1,81 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
 use k8s_openapi::api::authorization::v1::{
-    ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+    ResourceAttributes, ResourceRule, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+    SelfSubjectRulesReview, SelfSubjectRulesReviewSpec,
 };
 use kube::{Api, Client, Config, api::PostParams, config::AuthInfo};
 use tonic::{Status, metadata::MetadataMap};
 
+/// How long a cached authorization decision or pooled [`Client`] stays valid before the next
+/// check falls through to the k8s API again. Short enough that a revoked RBAC binding takes
+/// effect quickly, long enough that a UI opening many concurrent log streams for the same user
+/// doesn't hit the API server on every one.
+const AUTH_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Identifies one cached `(token, namespace, verb)` access decision. The raw bearer token never
+/// sits in memory as a key -- only its blake3 digest does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DecisionKey {
+    token_hash: [u8; 32],
+    namespace: String,
+    verb: String,
+}
+
+struct DecisionEntry {
+    allowed: bool,
+    expires_at: Instant,
+}
+
+struct ClientEntry {
+    client: Client,
+    expires_at: Instant,
+}
+
+/// Process-wide cache of `SelfSubjectAccessReview` results, shared across every [`Authorizer`]
+/// instance since each one is constructed fresh per gRPC call. A plain `Mutex<HashMap>` rather
+/// than a sharded map: authorization checks are not hot enough per-stream to justify `dashmap`,
+/// and stale entries are swept opportunistically on insert rather than via a background task.
+static DECISION_CACHE: LazyLock<Mutex<HashMap<DecisionKey, DecisionEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Process-wide pool of `kube::Client`s keyed by token digest, so that repeated calls with the
+/// same bearer token reuse the same underlying HTTP client instead of rebuilding one per stream.
+static CLIENT_CACHE: LazyLock<Mutex<HashMap<[u8; 32], ClientEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn hash_token(token: &str) -> [u8; 32] {
+    *blake3::hash(token.as_bytes()).as_bytes()
+}
+
+/// One namespace's allow/deny result for each verb requested by
+/// [`Authorizer::authorized_verbs`].
+pub type VerbDecisions = HashMap<String, bool>;
+
+/// Checks whether `rules` (a `SelfSubjectRulesReview`'s `resourceRules`) grant `verb` against the
+/// core-group `pods/log` resource. This only covers the subset of RBAC matching this authorizer
+/// cares about -- wildcard and exact verb/group/resource grants -- not the full
+/// `resourceNames`/field-selector semantics the API server itself evaluates.
+fn verb_allowed_by_rules(rules: &[ResourceRule], verb: &str) -> bool {
+    rules.iter().any(|rule| {
+        let verb_matches = rule.verbs.iter().any(|v| v == "*" || v == verb);
+        let group_matches = rule
+            .api_groups
+            .as_ref()
+            .is_some_and(|groups| groups.iter().any(|group| group == "*" || group.is_empty()));
+        let resource_matches = rule
+            .resources
+            .as_ref()
+            .is_some_and(|resources| resources.iter().any(|r| r == "*" || r == "pods/log"));
+        verb_matches && group_matches && resource_matches
+    })
+}
+
 pub struct Authorizer {
     k8s_config: Config,
+    token_hash: [u8; 32],
 }
 
 /// Checks that the the k8s doing the request has proper rights to access the log files.
@@ -25,6 +95,8 @@ impl Authorizer {
             })?
             .to_owned();
 
+        let token_hash = hash_token(&token);
+
         let mut k8s_config = Config::infer().await.map_err(|error| {
             Status::new(
                 tonic::Code::Unknown,
@@ -37,26 +109,105 @@ impl Authorizer {
             ..Default::default()
         };
 
-        Ok(Self { k8s_config })
+        Ok(Self {
+            k8s_config,
+            token_hash,
+        })
     }
 
-    /// Checks if the request is authorized by calling the k8s API.
+    /// Returns the cached decision for `(self.token_hash, namespace, verb)` if one exists and
+    /// hasn't expired, skipping the k8s API round-trip entirely.
+    fn cached_decision(&self, namespace: &str, verb: &str) -> Option<bool> {
+        let key = DecisionKey {
+            token_hash: self.token_hash,
+            namespace: namespace.to_owned(),
+            verb: verb.to_owned(),
+        };
+        let cache = DECISION_CACHE.lock().unwrap();
+        cache
+            .get(&key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.allowed)
+    }
+
+    /// Records a freshly-fetched decision, sweeping expired entries out of the cache first so it
+    /// doesn't grow unbounded across the life of the process.
+    fn cache_decision(&self, namespace: &str, verb: &str, allowed: bool) {
+        let key = DecisionKey {
+            token_hash: self.token_hash,
+            namespace: namespace.to_owned(),
+            verb: verb.to_owned(),
+        };
+        let mut cache = DECISION_CACHE.lock().unwrap();
+        cache.retain(|_, entry| entry.expires_at > Instant::now());
+        cache.insert(
+            key,
+            DecisionEntry {
+                allowed,
+                expires_at: Instant::now() + AUTH_CACHE_TTL,
+            },
+        );
+    }
+
+    /// Returns a `Client` for `self.k8s_config`, reusing a pooled one keyed by the caller's token
+    /// digest when it's still within [`AUTH_CACHE_TTL`] instead of rebuilding one per call.
+    fn pooled_client(&self) -> Result<Client, Status> {
+        let mut cache = CLIENT_CACHE.lock().unwrap();
+        if let Some(entry) = cache.get(&self.token_hash) {
+            if entry.expires_at > Instant::now() {
+                return Ok(entry.client.clone());
+            }
+        }
+
+        let client = Client::try_from(self.k8s_config.clone())
+            .map_err(|error| Status::new(tonic::Code::Unauthenticated, error.to_string()))?;
+
+        cache.retain(|_, entry| entry.expires_at > Instant::now());
+        cache.insert(
+            self.token_hash,
+            ClientEntry {
+                client: client.clone(),
+                expires_at: Instant::now() + AUTH_CACHE_TTL,
+            },
+        );
+
+        Ok(client)
+    }
+
+    /// Checks if the request is authorized, consulting the decision cache before falling back to
+    /// the k8s API for whichever `(namespace, verb)` pairs aren't cached.
     pub async fn is_authorized(
         &self,
         mut namespaces: &Vec<String>,
         verb: &str,
     ) -> Result<(), Status> {
-        let client = Client::try_from(self.k8s_config.clone())
-            .map_err(|error| Status::new(tonic::Code::Unauthenticated, error.to_string()))?;
-
         // Default to all namespaces if no namespace is provided.
         let empty_namespace = vec![String::new()];
         if namespaces.is_empty() {
             namespaces = &empty_namespace;
         }
 
-        let access_reviews: Api<SelfSubjectAccessReview> = Api::all(client);
+        let mut uncached = Vec::new();
         for namespace in namespaces {
+            match self.cached_decision(namespace, verb) {
+                Some(true) => continue,
+                Some(false) => {
+                    return Err(Status::new(
+                        tonic::Code::Unauthenticated,
+                        format!("permission denied: `{verb} pods/log` in namespace `{namespace}`"),
+                    ));
+                }
+                None => uncached.push(namespace),
+            }
+        }
+
+        if uncached.is_empty() {
+            return Ok(());
+        }
+
+        let client = self.pooled_client()?;
+        let access_reviews: Api<SelfSubjectAccessReview> = Api::all(client);
+        for namespace in uncached {
             let access_review = SelfSubjectAccessReview {
                 spec: SelfSubjectAccessReviewSpec {
                     resource_attributes: Some(ResourceAttributes {
@@ -81,7 +232,10 @@ impl Authorizer {
                     )
                 })?;
 
-            if response.status.is_none() || !response.status.unwrap().allowed {
+            let allowed = response.status.is_some_and(|status| status.allowed);
+            self.cache_decision(namespace, verb, allowed);
+
+            if !allowed {
                 return Err(Status::new(
                     tonic::Code::Unauthenticated,
                     format!("permission denied: `{verb} pods/log` in namespace `{namespace}`"),
@@ -91,6 +245,65 @@ impl Authorizer {
 
         Ok(())
     }
+
+    /// Checks every verb in `verbs` against `pods/log` in each namespace in `namespaces` with a
+    /// single `SelfSubjectRulesReview` per namespace, instead of one `SelfSubjectAccessReview`
+    /// per `(namespace, verb)` pair as [`Authorizer::is_authorized`] does. Unlike
+    /// `is_authorized`, this never fails fast on the first denial -- it returns every namespace's
+    /// full verb -> allowed map so a caller can decide what to do with partial access. Results
+    /// are also written into the shared decision cache, so a subsequent `is_authorized` call for
+    /// the same token/namespace/verb is a cache hit.
+    pub async fn authorized_verbs(
+        &self,
+        mut namespaces: &Vec<String>,
+        verbs: &[&str],
+    ) -> Result<HashMap<String, VerbDecisions>, Status> {
+        let empty_namespace = vec![String::new()];
+        if namespaces.is_empty() {
+            namespaces = &empty_namespace;
+        }
+
+        let client = self.pooled_client()?;
+        let rules_reviews: Api<SelfSubjectRulesReview> = Api::all(client);
+        let mut decisions = HashMap::with_capacity(namespaces.len());
+
+        for namespace in namespaces {
+            let review = SelfSubjectRulesReview {
+                spec: SelfSubjectRulesReviewSpec {
+                    namespace: Some(namespace.to_owned()),
+                },
+                ..SelfSubjectRulesReview::default()
+            };
+
+            let response = rules_reviews
+                .create(&PostParams::default(), &review)
+                .await
+                .map_err(|error| {
+                    Status::new(
+                        tonic::Code::Unknown,
+                        format!("failed to authenticate {error}"),
+                    )
+                })?;
+
+            let resource_rules = response
+                .status
+                .map(|status| status.resource_rules)
+                .unwrap_or_default();
+
+            let verb_decisions = verbs
+                .iter()
+                .map(|verb| {
+                    let allowed = verb_allowed_by_rules(&resource_rules, verb);
+                    self.cache_decision(namespace, verb, allowed);
+                    (verb.to_string(), allowed)
+                })
+                .collect();
+
+            decisions.insert(namespace.clone(), verb_decisions);
+        }
+
+        Ok(decisions)
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +311,7 @@ impl Authorizer {
     pub async fn new(_request_metadata: &MetadataMap) -> Result<Self, Status> {
         Ok(Self {
             k8s_config: Config::infer().await.unwrap(),
+            token_hash: [0; 32],
         })
     }
 
@@ -0,0 +1,92 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::sync::broadcast::Receiver as BcReceiver;
+use tracing::{info, warn};
+
+use crate::config::TlsConfig;
+
+/// A QUIC listener for serving gRPC over HTTP/3, selected by `cluster-agent.transport = "h3"`.
+///
+/// This module builds and accepts connections on a real `quinn::Endpoint`, reusing the same
+/// certificate material as the HTTP/2 listener via [`TlsConfig::build_acceptor`]. It does **not**
+/// bridge accepted h3 request/response streams onto tonic's `Routes` — that requires translating
+/// between `h3`'s stream types and the `http::Request`/`http::Body` shapes tonic's service router
+/// expects, which is a separate, substantial integration and is not implemented here. Accepted
+/// connections are logged and then dropped; no gRPC service in this binary is actually reachable
+/// over HTTP/3 yet.
+fn build_endpoint(
+    bind_addr: SocketAddr,
+    tls_config: &TlsConfig,
+) -> Result<Option<quinn::Endpoint>, Box<dyn Error>> {
+    let Some(acceptor) = tls_config.build_acceptor()? else {
+        return Ok(None);
+    };
+
+    let mut server_config = (*acceptor.config()).clone();
+    server_config.alpn_protocols = vec![b"h3".to_vec()];
+
+    let quic_server_config = quinn::crypto::rustls::QuicServerConfig::try_from(server_config)?;
+    let endpoint = quinn::Endpoint::server(
+        quinn::ServerConfig::with_crypto(Arc::new(quic_server_config)),
+        bind_addr,
+    )?;
+
+    Ok(Some(endpoint))
+}
+
+/// Accepts connections on `endpoint` until `term_rx` signals shutdown. See the module doc comment
+/// for what's deliberately not implemented: accepted connections are logged and dropped rather
+/// than routed to a gRPC service.
+async fn serve(endpoint: quinn::Endpoint, mut term_rx: BcReceiver<()>) {
+    loop {
+        tokio::select! {
+            biased;
+            _ = term_rx.recv() => {
+                endpoint.close(0u32.into(), b"server shutting down");
+                break;
+            }
+            maybe_connecting = endpoint.accept() => {
+                let Some(connecting) = maybe_connecting else {
+                    break;
+                };
+
+                tokio::spawn(async move {
+                    match connecting.await {
+                        Ok(connection) => {
+                            info!(
+                                "Accepted HTTP/3 connection from {}; dropping, request bridging \
+                                 not implemented",
+                                connection.remote_address()
+                            );
+                        }
+                        Err(error) => warn!("HTTP/3 handshake failed: {error}"),
+                    }
+                });
+            }
+        }
+    }
+
+    endpoint.wait_idle().await;
+}
+
+/// Builds and runs the HTTP/3 listener on `bind_addr`, or does nothing if TLS is disabled (QUIC
+/// has no non-TLS mode; [`Config::parse`](crate::config::Config::parse) already rejects
+/// `transport = "h3"` without `tls.enabled`, so this should only return `Ok(())` without spawning
+/// here if that validation is ever bypassed).
+pub async fn spawn(
+    bind_addr: SocketAddr,
+    tls_config: &TlsConfig,
+    term_rx: BcReceiver<()>,
+) -> Result<(), Box<dyn Error>> {
+    let Some(endpoint) = build_endpoint(bind_addr, tls_config)? else {
+        warn!("transport = \"h3\" requires tls.enabled; not starting HTTP/3 listener");
+        return Ok(());
+    };
+
+    info!("Starting HTTP/3 (QUIC) listener on {bind_addr}");
+    tokio::spawn(serve(endpoint, term_rx));
+
+    Ok(())
+}
@@ -1,30 +1,50 @@
-use notify::RecommendedWatcher;
+use flate2::read::GzDecoder;
 use prost_types::Timestamp;
 use regex::{Captures, Regex};
 use std::env;
 use std::fs::File;
+use std::io::{self, Read};
 use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 use tracing::debug;
 
 use tokio::fs::read_dir;
 use tokio::sync::broadcast::Sender;
+use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::{ReadDirStream, ReceiverStream};
 use tokio_util::task::TaskTracker;
 use tonic::{Request, Response, Status};
 use types::cluster_agent::log_metadata_service_server::LogMetadataService;
 use types::cluster_agent::{
-    LogMetadata, LogMetadataFileInfo, LogMetadataList, LogMetadataListRequest, LogMetadataSpec,
-    LogMetadataWatchEvent, LogMetadataWatchRequest,
+    LogMetadata, LogMetadataFileInfo, LogMetadataList, LogMetadataListRequest,
+    LogMetadataListStreamRequest, LogMetadataListStreamResponse, LogMetadataScanProgress,
+    LogMetadataSpec, LogMetadataWatchEvent, LogMetadataWatchRequest,
 };
 
 use crate::authorizer::Authorizer;
-use crate::log_metadata::log_metadata_watcher::LogMetadataWatcher;
+use crate::log_metadata::log_metadata_watcher::{LogMetadataWatcher, WatcherKind};
+use crate::log_metadata::retention::RetentionConfig;
+use crate::metrics::{LogMetadataMetrics, NamespaceLabels, WatchEventLabels};
 use crate::stream_util::wrap_with_shutdown;
 
-mod log_metadata_watcher;
+pub mod log_metadata_watcher;
+pub mod retention;
+
+/// Media type reported for a log file whose leading bytes carry the gzip magic number
+/// (`1f 8b`), the way kubelet leaves older rotated segments (`*.log.*.gz`).
+pub const GZIP_MEDIA_TYPE: &str = "application/gzip";
+/// Media type reported for a log file that doesn't match any known compressed format.
+pub const PLAIN_MEDIA_TYPE: &str = "text/plain";
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Files scanned and sent per `ListStream` batch. Small enough that a client sees steady progress
+/// on a directory with thousands of entries, large enough that the per-message overhead stays
+/// negligible.
+const LIST_STREAM_BATCH_SIZE: usize = 100;
 
 pub static LOG_FILE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
@@ -32,31 +52,99 @@ pub static LOG_FILE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         ).unwrap()
 });
 
+/// Named-capture groups `get_log_metadata_spec` requires out of every configured filename
+/// pattern, mirroring the groups [`LOG_FILE_REGEX`] itself declares.
+const REQUIRED_CAPTURE_GROUPS: [&str; 4] =
+    ["pod_name", "namespace", "container_name", "container_id"];
+
+/// Compiles `patterns` (e.g. from [`crate::config::WatcherConfig::log_filename_patterns`]) into
+/// the ordered list `get_log_metadata_spec` tries each filename against, validating that every
+/// pattern declares the capture groups it requires. An empty `patterns` falls back to the
+/// built-in CRI-style [`LOG_FILE_REGEX`], so clusters that don't customize this see no change in
+/// behavior. Letting a bad pattern fail fast here, at startup, is preferable to discovering it
+/// silently drops every log file once the agent is already serving traffic.
+pub fn compile_filename_patterns(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    if patterns.is_empty() {
+        return Ok(vec![LOG_FILE_REGEX.clone()]);
+    }
+
+    patterns
+        .iter()
+        .map(|pattern| {
+            let regex = Regex::new(pattern).map_err(|error| {
+                format!("invalid log filename pattern {pattern:?}: {error}")
+            })?;
+
+            for group in REQUIRED_CAPTURE_GROUPS {
+                if regex.capture_names().flatten().all(|name| name != group) {
+                    return Err(format!(
+                        "log filename pattern {pattern:?} is missing the required `{group}` capture group"
+                    ));
+                }
+            }
+
+            Ok(regex)
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct LogMetadataImpl {
     logs_dir: PathBuf,
     term_tx: Sender<()>,
     task_tracker: TaskTracker,
     node_name: String,
+    watcher_kind: WatcherKind,
+    debounce_interval: Duration,
+    recursive_watch: bool,
+    retention: RetentionConfig,
+    drain_timeout: Duration,
+    metrics: Arc<LogMetadataMetrics>,
+    /// Named-capture patterns tried in order against each log filename, compiled once by
+    /// [`compile_filename_patterns`]. Lets a cluster running a non-standard container runtime or
+    /// log-rotation convention configure its own layout instead of forking the crate.
+    filename_patterns: Arc<Vec<Regex>>,
 }
 
 impl LogMetadataImpl {
-    pub fn new(logs_dir: PathBuf, term_tx: Sender<()>, task_tracker: TaskTracker) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        logs_dir: PathBuf,
+        term_tx: Sender<()>,
+        task_tracker: TaskTracker,
+        watcher_kind: WatcherKind,
+        debounce_interval: Duration,
+        recursive_watch: bool,
+        retention: RetentionConfig,
+        drain_timeout: Duration,
+        metrics: Arc<LogMetadataMetrics>,
+        filename_patterns: Arc<Vec<Regex>>,
+    ) -> Self {
         Self {
             logs_dir,
             term_tx,
             task_tracker,
             node_name: env::var("NODE_NAME").unwrap_or_else(|_| "Env variable not set".to_owned()),
+            watcher_kind,
+            debounce_interval,
+            recursive_watch,
+            retention,
+            drain_timeout,
+            metrics,
+            filename_patterns,
         }
     }
 
     fn get_log_metadata_spec(
         filepath: &Path,
+        patterns: &[Regex],
         namespaces: &[String],
         node_name: &str,
     ) -> Option<LogMetadataSpec> {
         let filename = filepath.file_name()?.to_string_lossy();
-        let captures = LOG_FILE_REGEX.captures(filename.as_ref());
+        let captures = patterns
+            .iter()
+            .find_map(|pattern| pattern.captures(filename.as_ref()));
 
         if captures.is_none() {
             debug!("Filename could not be parsed: {}", filename.as_ref());
@@ -83,19 +171,64 @@ impl LogMetadataImpl {
     }
 
     fn get_file_info(filepath: &Path) -> Result<LogMetadataFileInfo, std::io::Error> {
-        let file = File::open(filepath)?;
+        let mut file = File::open(filepath)?;
         let metadata = file.metadata()?;
+        let last_modified_at = metadata.modified().ok().map(Timestamp::from);
+
+        if sniff_is_gzip(&mut file)? {
+            // The on-disk size is the compressed length; decompress to report the length and
+            // digest of the content a reader would actually see.
+            let mut decoder = GzDecoder::new(file);
+            let mut hasher = blake3::Hasher::new();
+            let size = io::copy(&mut decoder, &mut hasher).ok();
+            let digest = size.map(|_| hasher.finalize().to_hex().to_string());
+
+            return Ok(LogMetadataFileInfo {
+                size: size.unwrap_or(0).try_into().unwrap(),
+                last_modified_at,
+                digest,
+                media_type: Some(GZIP_MEDIA_TYPE.to_owned()),
+                inode: metadata.ino(),
+            });
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        let digest = hasher
+            .update_reader(&mut file)
+            .ok()
+            .map(|_| hasher.finalize().to_hex().to_string());
 
         Ok(LogMetadataFileInfo {
             size: metadata.size().try_into().unwrap(),
-            last_modified_at: metadata.modified().ok().map(Timestamp::from),
+            last_modified_at,
+            digest,
+            media_type: Some(PLAIN_MEDIA_TYPE.to_owned()),
+            inode: metadata.ino(),
         })
     }
 }
 
+// Sniffs the first two bytes of `file` for the gzip magic number, then rewinds so the caller can
+// still read the file from the start. Treated as "not gzip" rather than an error if the file is
+// shorter than the magic number (e.g. a freshly created, still-empty log file).
+fn sniff_is_gzip(file: &mut File) -> Result<bool, std::io::Error> {
+    use std::io::{Seek, SeekFrom};
+
+    let mut magic = [0u8; 2];
+    let is_gzip = match file.read_exact(&mut magic) {
+        Ok(()) => magic == GZIP_MAGIC,
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => false,
+        Err(error) => return Err(error),
+    };
+
+    file.seek(SeekFrom::Start(0))?;
+    Ok(is_gzip)
+}
+
 #[tonic::async_trait]
 impl LogMetadataService for LogMetadataImpl {
     type WatchStream = ReceiverStream<Result<LogMetadataWatchEvent, Status>>;
+    type ListStreamStream = ReceiverStream<Result<LogMetadataListStreamResponse, Status>>;
 
     #[tracing::instrument]
     async fn list(
@@ -140,9 +273,12 @@ impl LogMetadataService for LogMetadataImpl {
 
             let file = file.unwrap();
 
-            let Some(metadata_spec) =
-                Self::get_log_metadata_spec(&file.path(), &namespaces, &self.node_name)
-            else {
+            let Some(metadata_spec) = Self::get_log_metadata_spec(
+                &file.path(),
+                &self.filename_patterns,
+                &namespaces,
+                &self.node_name,
+            ) else {
                 continue;
             };
 
@@ -162,10 +298,21 @@ impl LogMetadataService for LogMetadataImpl {
                 }
             }
 
+            let file_info = file_info.unwrap();
+            let labels = NamespaceLabels {
+                namespace: metadata_spec.namespace.clone(),
+                node: self.node_name.clone(),
+            };
+            self.metrics.files_discovered.get_or_create(&labels).inc();
+            self.metrics
+                .bytes_observed
+                .get_or_create(&labels)
+                .inc_by(file_info.size.max(0) as u64);
+
             metadata_items.push(LogMetadata {
                 id: metadata_spec.container_id.clone(),
                 spec: Some(metadata_spec),
-                file_info: Some(file_info.unwrap()),
+                file_info: Some(file_info),
             });
         }
 
@@ -191,24 +338,226 @@ impl LogMetadataService for LogMetadataImpl {
 
         authorizer.is_authorized(&namespaces, "watch").await?;
 
+        let kind_filter: std::collections::HashSet<String> =
+            request.event_kinds.into_iter().filter(|kind| !kind.is_empty()).collect();
+
         let (log_metadata_watcher, log_metadata_rx) = LogMetadataWatcher::new(
             Path::new(&self.logs_dir).to_path_buf(),
             namespaces,
             term_tx,
             self.node_name.clone(),
+            self.watcher_kind,
+            self.debounce_interval,
+            self.recursive_watch,
+            request.last_digests,
+            self.retention,
+            kind_filter,
+            self.filename_patterns.clone(),
         );
 
+        // No control-plane signal reconfigures a per-stream watcher's namespaces today, so the
+        // command sender is simply dropped; the watcher logs and carries on as usual.
+        let (_command_tx, command_rx) = mpsc::channel(10);
+
+        self.metrics.watch_sessions_open.inc();
+        let metrics = self.metrics.clone();
         self.task_tracker.spawn(async move {
-            log_metadata_watcher.watch::<RecommendedWatcher>(None).await;
+            log_metadata_watcher.watch(command_rx).await;
+            metrics.watch_sessions_open.dec();
         });
 
+        let log_metadata_rx = self.tap_watch_events(log_metadata_rx);
+
         Ok(Response::new(wrap_with_shutdown(
             log_metadata_rx,
             self.term_tx.clone(),
+            self.drain_timeout,
+        )))
+    }
+
+    /// Resumable, progress-reporting alternative to `list` for directories too large to collect
+    /// and return in a single response. The directory is snapshotted and sorted once up front so
+    /// `cursor` (the name of the last file sent) resumes deterministically; the scan itself runs
+    /// as a spawned task that checks `term_tx` between batches so it aborts promptly on agent
+    /// shutdown instead of running to completion.
+    #[tracing::instrument]
+    async fn list_stream(
+        &self,
+        request: Request<LogMetadataListStreamRequest>,
+    ) -> Result<Response<Self::ListStreamStream>, Status> {
+        let authorizer = Authorizer::new(request.metadata()).await?;
+        let request = request.into_inner();
+
+        if !self.logs_dir.is_dir() {
+            return Err(Status::new(
+                tonic::Code::NotFound,
+                format!(
+                    "Log directory not found: {}",
+                    self.logs_dir.to_string_lossy()
+                ),
+            ));
+        }
+
+        let namespaces: Vec<String> = request
+            .namespaces
+            .into_iter()
+            .filter(|namespace| !namespace.is_empty())
+            .collect();
+
+        authorizer.is_authorized(&namespaces, "list").await?;
+
+        let mut files = ReadDirStream::new(read_dir(&self.logs_dir).await?);
+        let mut paths = Vec::new();
+
+        while let Some(file) = files.next().await {
+            match file {
+                Ok(file) => paths.push(file.path()),
+                Err(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => {
+                    debug!("Could not open file: {}", io_error);
+                }
+                Err(io_error) => return Err(io_error.into()),
+            }
+        }
+        paths.sort();
+
+        let start_index = if request.cursor.is_empty() {
+            0
+        } else {
+            paths
+                .iter()
+                .position(|path| {
+                    path.file_name().map(|name| name.to_string_lossy().into_owned())
+                        == Some(request.cursor.clone())
+                })
+                .map(|index| index + 1)
+                .unwrap_or(0)
+        };
+
+        let files_total = paths.len() as i64;
+        let node_name = self.node_name.clone();
+        let metrics = self.metrics.clone();
+        let filename_patterns = self.filename_patterns.clone();
+        let mut term_rx = self.term_tx.subscribe();
+
+        let (tx, rx) = mpsc::channel(10);
+
+        self.task_tracker.spawn(async move {
+            let mut files_scanned = start_index as i64;
+            let mut bytes_accounted: i64 = 0;
+            let mut cursor = request.cursor;
+
+            for batch in paths[start_index..].chunks(LIST_STREAM_BATCH_SIZE) {
+                if term_rx.try_recv().is_ok() {
+                    break;
+                }
+
+                let mut items = Vec::new();
+                let mut warnings = Vec::new();
+
+                for path in batch {
+                    files_scanned += 1;
+                    if let Some(name) = path.file_name() {
+                        cursor = name.to_string_lossy().into_owned();
+                    }
+
+                    let Some(metadata_spec) =
+                        Self::get_log_metadata_spec(path, &filename_patterns, &namespaces, &node_name)
+                    else {
+                        continue;
+                    };
+
+                    match Self::get_file_info(path) {
+                        Ok(file_info) => {
+                            let labels = NamespaceLabels {
+                                namespace: metadata_spec.namespace.clone(),
+                                node: node_name.clone(),
+                            };
+                            metrics.files_discovered.get_or_create(&labels).inc();
+                            bytes_accounted += file_info.size.max(0);
+                            metrics
+                                .bytes_observed
+                                .get_or_create(&labels)
+                                .inc_by(file_info.size.max(0) as u64);
+
+                            items.push(LogMetadata {
+                                id: metadata_spec.container_id.clone(),
+                                spec: Some(metadata_spec),
+                                file_info: Some(file_info),
+                            });
+                        }
+                        Err(io_error) => {
+                            warnings.push(format!("{}: {}", path.to_string_lossy(), io_error));
+                        }
+                    }
+                }
+
+                let response = LogMetadataListStreamResponse {
+                    items,
+                    progress: Some(LogMetadataScanProgress {
+                        files_scanned,
+                        files_total,
+                        bytes_accounted,
+                    }),
+                    warnings,
+                    cursor: cursor.clone(),
+                };
+
+                if tx.send(Ok(response)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(wrap_with_shutdown(
+            rx,
+            self.term_tx.clone(),
+            self.drain_timeout,
         )))
     }
 }
 
+impl LogMetadataImpl {
+    /// Relays `rx` onto a freshly spawned channel, counting each event in `metrics.watch_events_total`
+    /// by namespace/kind along the way. Done as a pass-through tap rather than inside
+    /// `LogMetadataWatcher` itself, so the watcher stays unaware of metrics entirely.
+    fn tap_watch_events(
+        &self,
+        mut rx: mpsc::Receiver<Result<LogMetadataWatchEvent, Status>>,
+    ) -> mpsc::Receiver<Result<LogMetadataWatchEvent, Status>> {
+        let (tx, tapped_rx) = mpsc::channel(100);
+        let metrics = self.metrics.clone();
+        let node_name = self.node_name.clone();
+
+        self.task_tracker.spawn(async move {
+            while let Some(result) = rx.recv().await {
+                if let Ok(event) = &result {
+                    if let Some(namespace) = event
+                        .object
+                        .as_ref()
+                        .and_then(|object| object.spec.as_ref())
+                        .map(|spec| spec.namespace.clone())
+                    {
+                        metrics
+                            .watch_events_total
+                            .get_or_create(&WatchEventLabels {
+                                namespace,
+                                node: node_name.clone(),
+                                kind: event.r#type.clone(),
+                            })
+                            .inc();
+                    }
+                }
+
+                if tx.send(result).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        tapped_rx
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::log_metadata::LogMetadataImpl;
@@ -248,6 +597,14 @@ mod test {
             term_tx,
             task_tracker: TaskTracker::new(),
             node_name: "Node name".to_owned(),
+            watcher_kind: crate::log_metadata::log_metadata_watcher::WatcherKind::Native,
+            debounce_interval: std::time::Duration::from_secs(2),
+            recursive_watch: false,
+            retention: crate::log_metadata::retention::RetentionConfig::disabled(),
+            drain_timeout: std::time::Duration::from_secs(5),
+            metrics: std::sync::Arc::new(crate::metrics::LogMetadataMetrics::new(
+                &mut prometheus_client::registry::Registry::default(),
+            )),
         };
 
         let mut result = metadata_service
@@ -304,6 +661,14 @@ mod test {
             term_tx,
             task_tracker: TaskTracker::new(),
             node_name: "Node name".to_owned(),
+            watcher_kind: crate::log_metadata::log_metadata_watcher::WatcherKind::Native,
+            debounce_interval: std::time::Duration::from_secs(2),
+            recursive_watch: false,
+            retention: crate::log_metadata::retention::RetentionConfig::disabled(),
+            drain_timeout: std::time::Duration::from_secs(5),
+            metrics: std::sync::Arc::new(crate::metrics::LogMetadataMetrics::new(
+                &mut prometheus_client::registry::Registry::default(),
+            )),
         };
 
         let mut result = metadata_service
@@ -368,6 +733,14 @@ mod test {
             term_tx,
             task_tracker: TaskTracker::new(),
             node_name: "Node name".to_owned(),
+            watcher_kind: crate::log_metadata::log_metadata_watcher::WatcherKind::Native,
+            debounce_interval: std::time::Duration::from_secs(2),
+            recursive_watch: false,
+            retention: crate::log_metadata::retention::RetentionConfig::disabled(),
+            drain_timeout: std::time::Duration::from_secs(5),
+            metrics: std::sync::Arc::new(crate::metrics::LogMetadataMetrics::new(
+                &mut prometheus_client::registry::Registry::default(),
+            )),
         };
 
         let result = metadata_service
@@ -395,4 +768,188 @@ mod test {
 
         assert_eq!(2, filtered_files.len());
     }
+
+    /// Unlike [`create_test_file`], puts the file in a dedicated directory rather than the shared
+    /// system temp dir, so a `list_stream` test's `files_total` isn't thrown off by files left
+    /// behind by unrelated tests running in parallel.
+    fn create_test_file_in(dir: &std::path::Path, name: &str, num_bytes: usize) -> NamedTempFile {
+        let mut test_file = Builder::new()
+            .prefix(name)
+            .suffix(".log")
+            .tempfile_in(dir)
+            .expect("Failed to create file");
+
+        test_file
+            .write_all(&vec![0; num_bytes])
+            .expect("Failed to write to file");
+
+        test_file
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_list_stream_reports_progress_and_cursor() {
+        use tokio_stream::StreamExt;
+        use types::cluster_agent::LogMetadataListStreamRequest;
+
+        let dir = tempfile::tempdir().unwrap();
+        let _first_file = create_test_file_in(
+            dir.path(),
+            "pod-name_stream-firstnamespace_container-name1-containerid1",
+            4,
+        );
+        let _second_file = create_test_file_in(
+            dir.path(),
+            "pod-name_stream-secondnamespace_container-name2-containerid2",
+            4,
+        );
+
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = dir.path().to_path_buf();
+
+        let metadata_service = LogMetadataImpl {
+            logs_dir,
+            term_tx,
+            task_tracker: TaskTracker::new(),
+            node_name: "Node name".to_owned(),
+            watcher_kind: crate::log_metadata::log_metadata_watcher::WatcherKind::Native,
+            debounce_interval: std::time::Duration::from_secs(2),
+            recursive_watch: false,
+            retention: crate::log_metadata::retention::RetentionConfig::disabled(),
+            drain_timeout: std::time::Duration::from_secs(5),
+            metrics: std::sync::Arc::new(crate::metrics::LogMetadataMetrics::new(
+                &mut prometheus_client::registry::Registry::default(),
+            )),
+        };
+
+        let mut stream = metadata_service
+            .list_stream(Request::new(LogMetadataListStreamRequest {
+                namespaces: vec![
+                    "stream-firstnamespace".into(),
+                    "stream-secondnamespace".into(),
+                ],
+                cursor: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let response = stream.next().await.unwrap().unwrap();
+
+        assert_eq!(2, response.items.len());
+        assert!(!response.cursor.is_empty());
+
+        let progress = response.progress.unwrap();
+        assert_eq!(2, progress.files_scanned);
+        assert_eq!(2, progress.files_total);
+        assert_eq!(8, progress.bytes_accounted);
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_list_stream_resumes_from_cursor() {
+        use tokio_stream::StreamExt;
+        use types::cluster_agent::LogMetadataListStreamRequest;
+
+        let dir = tempfile::tempdir().unwrap();
+        let first_file = create_test_file_in(
+            dir.path(),
+            "a-pod-name_resume-namespace_container-name1-containerid1",
+            4,
+        );
+        let second_file = create_test_file_in(
+            dir.path(),
+            "b-pod-name_resume-namespace_container-name2-containerid2",
+            4,
+        );
+        let logs_dir = dir.path().to_path_buf();
+
+        // Directory entries are sorted before scanning, so resuming after the first file's cursor
+        // should only surface the second.
+        let cursor = first_file
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+
+        let (term_tx, _term_rx) = broadcast::channel(1);
+
+        let metadata_service = LogMetadataImpl {
+            logs_dir,
+            term_tx,
+            task_tracker: TaskTracker::new(),
+            node_name: "Node name".to_owned(),
+            watcher_kind: crate::log_metadata::log_metadata_watcher::WatcherKind::Native,
+            debounce_interval: std::time::Duration::from_secs(2),
+            recursive_watch: false,
+            retention: crate::log_metadata::retention::RetentionConfig::disabled(),
+            drain_timeout: std::time::Duration::from_secs(5),
+            metrics: std::sync::Arc::new(crate::metrics::LogMetadataMetrics::new(
+                &mut prometheus_client::registry::Registry::default(),
+            )),
+        };
+
+        let mut stream = metadata_service
+            .list_stream(Request::new(LogMetadataListStreamRequest {
+                namespaces: vec!["resume-namespace".into()],
+                cursor,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let response = stream.next().await.unwrap().unwrap();
+
+        assert_eq!(1, response.items.len());
+        assert!(
+            response.items[0]
+                .spec
+                .as_ref()
+                .unwrap()
+                .container_id
+                .starts_with("containerid2")
+        );
+        assert_eq!(
+            second_file
+                .path()
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+            response.cursor
+        );
+
+        let progress = response.progress.unwrap();
+        assert_eq!(2, progress.files_scanned);
+        assert_eq!(2, progress.files_total);
+    }
+
+    #[test]
+    fn test_get_file_info_decompresses_gzip_segment() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        let plain = create_test_file("pod-name_gzip-namespace_container-name-containerid", 0);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"hello rotated log\n").unwrap();
+        let gzipped = encoder.finish().unwrap();
+        std::fs::write(plain.path(), &gzipped).unwrap();
+
+        let file_info = LogMetadataImpl::get_file_info(plain.path()).unwrap();
+
+        assert_eq!(file_info.media_type.as_deref(), Some(super::GZIP_MEDIA_TYPE));
+        assert_eq!(file_info.size, "hello rotated log\n".len() as i64);
+        assert!(file_info.digest.is_some());
+    }
+
+    #[test]
+    fn test_get_file_info_reports_plain_media_type() {
+        let file = create_test_file("pod-name_plain-namespace_container-name-containerid", 4);
+
+        let file_info = LogMetadataImpl::get_file_info(file.path()).unwrap();
+
+        assert_eq!(file_info.media_type.as_deref(), Some(super::PLAIN_MEDIA_TYPE));
+        assert_eq!(file_info.size, 4);
+    }
 }
@@ -1,12 +1,17 @@
 use std::{
-    collections::{HashSet, VecDeque},
-    io,
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
-use notify::{Event, EventKind, RecursiveMode, Watcher};
+use indexmap::IndexMap;
+use notify::{Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{DebounceEventResult, Debouncer, RecommendedCache, new_debouncer_opt};
+use regex::Regex;
 use thiserror::Error;
 use tokio::{
     fs::read_dir,
@@ -20,9 +25,42 @@ use tokio::{
 use tokio_stream::{StreamExt, wrappers::ReadDirStream};
 use tonic::Status;
 use tracing::{debug, warn};
-use types::cluster_agent::{LogMetadata, LogMetadataFileInfo, LogMetadataWatchEvent};
+use types::cluster_agent::{LogMetadata, LogMetadataFileInfo, LogMetadataSpec, LogMetadataWatchEvent};
+
+use crate::log_metadata::retention::{self, RetentionConfig};
+use crate::log_metadata::{GZIP_MEDIA_TYPE, LOG_FILE_REGEX, LogMetadataImpl};
+
+/// Selects which notify backend the watcher uses under the hood.
+///
+/// Native (inotify/FSEvents/etc.) is the default and is the cheapest option, but it is blind on
+/// NFS, overlay, and some CSI-backed volumes that don't deliver filesystem events reliably. `Poll`
+/// trades CPU for reliability by stat-ing the watched paths on a fixed interval instead.
+#[derive(Debug, Clone, Copy)]
+pub enum WatcherKind {
+    /// OS-native filesystem event backend.
+    Native,
+    /// Stat-based polling with the given interval between scans.
+    Poll(Duration),
+}
 
-use crate::log_metadata::{LOG_FILE_REGEX, LogMetadataImpl};
+/// A command sent over the watcher's control channel to reconfigure it while it is running.
+#[derive(Debug, Clone)]
+pub enum WatcherCommand {
+    /// Start watching an additional k8s namespace.
+    AddNamespace(String),
+    /// Stop watching a k8s namespace. Emits synthetic `DELETED` events for its files so clients
+    /// drop them.
+    RemoveNamespace(String),
+    /// Re-scan the directory for files matching the currently configured namespaces and watch
+    /// any that are missing.
+    Rescan,
+    /// Immediately drain and process any events the debouncer is currently holding, rather than
+    /// waiting out the rest of `debounce_interval`. For latency-sensitive callers (an interactive
+    /// `tail -f`-style consumer) the fixed debounce window is a guaranteed tail latency on every
+    /// update; a flush lets them force prompt delivery on demand while batched bulk consumers
+    /// keep the cheap, default window.
+    Flush,
+}
 
 /// Uses notify crate internally to provide notifications of file updates.
 #[derive(Debug)]
@@ -31,21 +69,85 @@ pub struct LogMetadataWatcher {
     log_metadata_tx: Sender<Result<LogMetadataWatchEvent, Status>>,
     /// Channel to receive a termination signal and end the watch loop.
     term_tx: BcSender<()>,
-    /// K8s namespaces to watch for.
-    namespaces: Vec<String>,
+    /// K8s namespaces to watch for. Shared with the debounce callback so that namespace changes
+    /// made via `WatcherCommand` are picked up without tearing down the watcher.
+    namespaces: Arc<Mutex<Vec<String>>>,
     /// Directory to watch for updates.
     directory: PathBuf,
     /// K8s node name.
     node_name: String,
+    /// Which notify backend to construct the watcher with.
+    watcher_kind: WatcherKind,
+    /// Maximum time events for a path may be buffered before being flushed regardless of activity.
+    debounce_interval: Duration,
+    /// Whether to watch `directory` recursively, for layouts that nest log files under per-pod or
+    /// per-container subdirectories instead of a flat directory.
+    recursive: bool,
+    /// Size-based retention policy applied to watched files before they're inspected, so a file
+    /// that has grown past `max_size` is rotated aside here rather than by an external process.
+    retention: RetentionConfig,
+    /// Per-path rolling blake3 state, so the digest in each `LogMetadataFileInfo` can be updated
+    /// incrementally from the newly appended bytes instead of re-hashing the whole file.
+    digest_cache: Arc<Mutex<HashMap<PathBuf, DigestState>>>,
+    /// The last digest the caller reported having seen for each container_id. A `MODIFIED` event
+    /// whose freshly computed digest matches is suppressed, like an If-None-Match/ETag check.
+    last_digests: HashMap<String, String>,
+    /// Last observed (device, inode, size) for each logical container identity (the
+    /// namespace/pod/container triple), so a rename-based or copytruncate-style rotation can be
+    /// reported as a dedicated `ROTATED` event instead of a DELETED followed by an ADDED.
+    rotation_cache: Arc<Mutex<HashMap<(String, String, String), RotationState>>>,
+    /// The set of paths the debouncer currently has a watch on, maintained alongside every
+    /// watch/unwatch call. Used as the "before" side of the diff when `resync` reconciles against
+    /// the filesystem after an inotify queue overflow.
+    watched_paths: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Event types (`"ADDED"`/`"MODIFIED"`/`"DELETED"`/`"ROTATED"`) the caller wants to receive.
+    /// Empty means no filtering, i.e. every kind is sent, matching how an empty `namespaces` means
+    /// "every namespace".
+    kind_filter: HashSet<String>,
+    /// Named-capture patterns tried in order against each log filename; see
+    /// [`crate::log_metadata::compile_filename_patterns`].
+    filename_patterns: Arc<Vec<Regex>>,
 }
 
 impl LogMetadataWatcher {
     /// Returns a new watcher and a channel to receive log metadata updates.
+    ///
+    /// `debounce_interval` caps how long events for a path can be buffered. Events are actually
+    /// flushed sooner, as soon as a path has been quiet for a short settle window (a quarter of
+    /// `debounce_interval`), so idle-then-single-write files are reported promptly instead of
+    /// always paying the full interval as tail latency. A caller that can't tolerate even that can
+    /// send `WatcherCommand::Flush` to drain pending events immediately on demand.
+    ///
+    /// `recursive` opts into watching `directory` and its subdirectories, for runtimes that lay
+    /// out log files in nested per-pod/per-container subdirectories rather than a flat directory.
+    ///
+    /// `last_digests` carries the digest the caller last observed for each container_id (e.g.
+    /// from before a reconnect); a `MODIFIED` event for one of those containers whose content
+    /// hasn't actually changed is suppressed rather than re-streamed.
+    ///
+    /// `retention` is checked against a watched file on every write before it's otherwise
+    /// inspected; a file that has grown past its `max_size` is rotated aside, which naturally
+    /// surfaces as the same `ROTATED` event an externally-rotated file would produce.
+    ///
+    /// `kind_filter` restricts the emitted stream to the given event types (e.g. `["ADDED",
+    /// "DELETED"]` for a UI that only cares about pod lifecycle, not every append); an empty set
+    /// sends every kind, the same "unset means unfiltered" convention `namespaces` already uses.
+    ///
+    /// `filename_patterns` is tried, in order, against every log filename instead of the built-in
+    /// CRI layout alone; see [`crate::log_metadata::compile_filename_patterns`].
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         directory: PathBuf,
         namespaces: Vec<String>,
         term_tx: BcSender<()>,
         node_name: String,
+        watcher_kind: WatcherKind,
+        debounce_interval: Duration,
+        recursive: bool,
+        last_digests: HashMap<String, String>,
+        retention: RetentionConfig,
+        kind_filter: HashSet<String>,
+        filename_patterns: Arc<Vec<Regex>>,
     ) -> (Self, Receiver<Result<LogMetadataWatchEvent, Status>>) {
         let (log_metadata_tx, log_metadata_rx) = channel(100);
 
@@ -53,71 +155,237 @@ impl LogMetadataWatcher {
             Self {
                 log_metadata_tx,
                 term_tx,
-                namespaces,
+                namespaces: Arc::new(Mutex::new(namespaces)),
                 directory,
                 node_name,
+                watcher_kind,
+                debounce_interval,
+                recursive,
+                retention,
+                digest_cache: Arc::new(Mutex::new(HashMap::new())),
+                last_digests,
+                rotation_cache: Arc::new(Mutex::new(HashMap::new())),
+                watched_paths: Arc::new(Mutex::new(HashSet::new())),
+                kind_filter,
+                filename_patterns,
             },
             log_metadata_rx,
         )
     }
 
+    /// Sends `event` unless its type isn't in `kind_filter` (empty means unfiltered), in which
+    /// case it's silently dropped. Returns `false` if the client's receiver has been dropped, the
+    /// same signal a direct `log_metadata_tx.send(...).await.is_err()` check gives, so callers can
+    /// keep using it to decide whether to keep going.
+    async fn send_event(&self, event: LogMetadataWatchEvent) -> bool {
+        if !self.kind_filter.is_empty() && !self.kind_filter.contains(&event.r#type) {
+            return true;
+        }
+
+        self.log_metadata_tx.send(Ok(event)).await.is_ok()
+    }
+
+    fn namespaces_snapshot(&self) -> Vec<String> {
+        self.namespaces.lock().unwrap().clone()
+    }
+
     /// Starts watching the log directory for log updates. Blocks until a message is sent in the
     /// termination channel.
-    pub async fn watch<T: Watcher>(&self, watcher_config: Option<notify::Config>) {
+    ///
+    /// Before entering the watch loop, sends an `ADDED` event for every log file that already
+    /// exists in the directory, so that a client that starts watching always sees a complete,
+    /// self-consistent view of the filesystem: the initial snapshot plus subsequent incremental
+    /// events always equals the current state on disk.
+    pub async fn watch(&self, command_rx: Receiver<WatcherCommand>) {
         let (internal_tx, internal_rx) = channel(10);
-        let debouncer: Debouncer<T, RecommendedCache> =
-            match self.setup_notify_watcher(internal_tx, watcher_config).await {
-                Ok(debouncer) => debouncer,
-                Err(watcher_error) => {
-                    let _ = self.log_metadata_tx.send(Err(watcher_error.into())).await;
+        let (debouncer, existing_paths) = match self.setup_notify_watcher(internal_tx).await {
+            Ok(result) => result,
+            Err(watcher_error) => {
+                let _ = self.log_metadata_tx.send(Err(watcher_error.into())).await;
+                return;
+            }
+        };
+
+        let namespaces_snapshot = self.namespaces_snapshot();
+        for path in &existing_paths {
+            if let Some(event) =
+                self.build_event(path, &namespaces_snapshot, LogMetadataWatchEventType::Added)
+            {
+                if !self.send_event(event).await {
+                    debug!("Channel closed from client.");
                     return;
                 }
-            };
+            }
+        }
 
         let term_rx = self.term_tx.subscribe();
 
-        self.listen_for_changes(internal_rx, debouncer, term_rx)
+        self.listen_for_changes(internal_rx, debouncer, term_rx, command_rx)
             .await;
     }
 
+    // Builds a LogMetadataWatchEvent for a file whose namespace is in `namespaces`. Used both for
+    // the startup snapshot (ADDED) and for namespace removal (DELETED).
+    fn build_event(
+        &self,
+        path: &Path,
+        namespaces: &[String],
+        event_type: LogMetadataWatchEventType,
+    ) -> Option<LogMetadataWatchEvent> {
+        let metadata_spec =
+            LogMetadataImpl::get_log_metadata_spec(
+                path,
+                &self.filename_patterns,
+                namespaces,
+                &self.node_name,
+            )?;
+
+        if matches!(event_type, LogMetadataWatchEventType::Deleted) {
+            self.digest_cache.lock().unwrap().remove(path);
+        }
+
+        // For a Deleted event the file is already gone by definition, so a placeholder file_info
+        // is the best we can do. For Added/Modified, a NotFound here means the file vanished in
+        // the race between discovery and inspection (e.g. a log rotated out from under us) -
+        // fabricating a zero-size file_info would tell clients about a file that was never
+        // really there, so we drop the event instead, mirroring how list() skips such files.
+        let mut file_info = match LogMetadataImpl::get_file_info(path) {
+            Ok(file_info) => file_info,
+            Err(_) if matches!(event_type, LogMetadataWatchEventType::Deleted) => {
+                LogMetadataFileInfo {
+                    size: 0,
+                    last_modified_at: None,
+                    digest: None,
+                    media_type: None,
+                    inode: 0,
+                }
+            }
+            Err(_) => return None,
+        };
+
+        // A gzip-compressed segment is already fully hashed over its decompressed content by
+        // get_file_info; the rolling cache only makes sense for a plaintext file that's
+        // growing in place.
+        if !matches!(event_type, LogMetadataWatchEventType::Deleted)
+            && file_info.media_type.as_deref() != Some(GZIP_MEDIA_TYPE)
+        {
+            file_info.digest = rolling_digest(&self.digest_cache, path);
+        }
+
+        Some(LogMetadataWatchEvent {
+            r#type: event_type.as_str().to_owned(),
+            object: Some(LogMetadata {
+                id: metadata_spec.container_id.clone(),
+                spec: Some(metadata_spec),
+                file_info: Some(file_info),
+            }),
+            previous_size: None,
+        })
+    }
+
     /// Creates the notify fs watcher and adds to the watch list all files
     /// that have the correct k8s namespace.
     ///
     /// # Arguments
     ///
     /// * `internal_tx` - The sender to use to propagate filesystem updates.
-    async fn setup_notify_watcher<T: Watcher>(
+    async fn setup_notify_watcher(
         &self,
         internal_tx: Sender<VecDeque<Result<LogMetadataWatchEvent, WatcherError>>>,
-        watcher_config: Option<notify::Config>,
-    ) -> Result<Debouncer<T, RecommendedCache>, WatcherError> {
-        let runtime_handle = Handle::current();
-        let namespaces = self.namespaces.clone();
-        let node_name = self.node_name.clone();
-
-        let mut debouncer = new_debouncer_opt(
-            Duration::from_secs(2),
-            None,
-            move |result: DebounceEventResult| {
-                runtime_handle.block_on(async {
-                    let _ = internal_tx
-                        .send(handle_debounced_events(result, &namespaces, &node_name))
-                        .await;
-                });
-            },
-            RecommendedCache::new(),
-            watcher_config.unwrap_or_default(),
-        )?;
+    ) -> Result<(WatcherHandle, Vec<PathBuf>), WatcherError> {
+        let mut debouncer = match self.watcher_kind {
+            WatcherKind::Native => {
+                let runtime_handle = Handle::current();
+                let namespaces = self.namespaces.clone();
+                let node_name = self.node_name.clone();
+                let digest_cache = self.digest_cache.clone();
+                let last_digests = self.last_digests.clone();
+                let rotation_cache = self.rotation_cache.clone();
+                let retention = self.retention;
+                let filename_patterns = self.filename_patterns.clone();
+
+                WatcherHandle::Native(new_debouncer_opt::<_, RecommendedWatcher, _>(
+                    self.debounce_interval,
+                    Some(self.debounce_interval / 4),
+                    move |result: DebounceEventResult| {
+                        let namespaces = namespaces.lock().unwrap().clone();
+                        runtime_handle.block_on(async {
+                            let _ = internal_tx
+                                .send(handle_debounced_events(
+                                    result,
+                                    &namespaces,
+                                    &node_name,
+                                    &digest_cache,
+                                    &last_digests,
+                                    &rotation_cache,
+                                    &retention,
+                                    &filename_patterns,
+                                ))
+                                .await;
+                        });
+                    },
+                    RecommendedCache::new(),
+                    notify::Config::default(),
+                )?)
+            }
+            WatcherKind::Poll(poll_interval) => {
+                let runtime_handle = Handle::current();
+                let namespaces = self.namespaces.clone();
+                let node_name = self.node_name.clone();
+                let digest_cache = self.digest_cache.clone();
+                let last_digests = self.last_digests.clone();
+                let rotation_cache = self.rotation_cache.clone();
+                let retention = self.retention;
+                let filename_patterns = self.filename_patterns.clone();
+
+                WatcherHandle::Poll(new_debouncer_opt::<_, PollWatcher, _>(
+                    self.debounce_interval,
+                    Some(self.debounce_interval / 4),
+                    move |result: DebounceEventResult| {
+                        let namespaces = namespaces.lock().unwrap().clone();
+                        runtime_handle.block_on(async {
+                            let _ = internal_tx
+                                .send(handle_debounced_events(
+                                    result,
+                                    &namespaces,
+                                    &node_name,
+                                    &digest_cache,
+                                    &last_digests,
+                                    &rotation_cache,
+                                    &retention,
+                                    &filename_patterns,
+                                ))
+                                .await;
+                        });
+                    },
+                    RecommendedCache::new(),
+                    notify::Config::default().with_poll_interval(poll_interval),
+                )?)
+            }
+        };
 
-        let paths_to_add = find_log_files(&self.directory, &self.namespaces).await?;
+        let paths_to_add = find_log_files(
+            &self.directory,
+            &self.namespaces_snapshot(),
+            self.recursive,
+            &self.filename_patterns,
+        )
+        .await?;
 
-        for path in paths_to_add {
+        for path in &paths_to_add {
             debouncer.watch(&path, notify::RecursiveMode::NonRecursive)?;
         }
 
-        debouncer.watch(&self.directory, notify::RecursiveMode::NonRecursive)?;
+        *self.watched_paths.lock().unwrap() = paths_to_add.iter().cloned().collect();
 
-        Ok(debouncer)
+        let directory_mode = if self.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        debouncer.watch(&self.directory, directory_mode)?;
+
+        Ok((debouncer, paths_to_add))
     }
 
     /// Blocks and listens for notify fs changes until either a message is sent to `term_rx` or
@@ -128,22 +396,34 @@ impl LogMetadataWatcher {
     /// * `internal_rx` - Receiver of filesystem updates.
     /// * `debouncer` - The notify filesystem watcher.
     /// * `term_rx` - Receiver of the termination channel.
-    async fn listen_for_changes<T: Watcher>(
+    async fn listen_for_changes(
         &self,
         mut internal_rx: Receiver<VecDeque<Result<LogMetadataWatchEvent, WatcherError>>>,
-        mut debouncer: Debouncer<T, RecommendedCache>,
+        mut debouncer: WatcherHandle,
         mut term_rx: tokio::sync::broadcast::Receiver<()>,
+        mut command_rx: Receiver<WatcherCommand>,
     ) {
         'outer: loop {
             select! {
                 metadata_events = internal_rx.recv() => {
                     if let Some(metadata_events) = metadata_events {
                         for metadata_event in metadata_events {
+                            if matches!(metadata_event, Err(WatcherError::QueueOverflow)) {
+                                debug!("Watch queue overflowed; resyncing against the filesystem");
+                                self.resync(&mut debouncer).await;
+                                continue;
+                            }
+
                             if let Ok(ref metadata_event) = metadata_event {
-                                self.update_watcher(metadata_event, &mut debouncer);
+                                self.update_watcher(metadata_event, &mut debouncer).await;
                             }
 
-                            if self.log_metadata_tx.send(metadata_event.map_err(Status::from)).await.is_err() {
+                            let still_open = match metadata_event {
+                                Ok(event) => self.send_event(event).await,
+                                Err(error) => self.log_metadata_tx.send(Err(error.into())).await.is_ok(),
+                            };
+
+                            if !still_open {
                                     debug!("Channel closed from client.");
                                     break 'outer;
                             }
@@ -153,6 +433,12 @@ impl LogMetadataWatcher {
                         break;
                     }
                 }
+                command = command_rx.recv() => {
+                    match command {
+                        Some(command) => self.handle_command(command, &mut debouncer).await,
+                        None => debug!("Command channel closed."),
+                    }
+                }
                 _ = term_rx.recv() => {
                         debug!("Received termination message");
                         let shutdown_status = Status::new(tonic::Code::Unavailable, "Server is shutting down");
@@ -166,26 +452,174 @@ impl LogMetadataWatcher {
         debouncer.stop();
     }
 
+    // Applies a WatcherCommand, reconfiguring the set of watched namespaces without tearing down
+    // the gRPC stream.
+    async fn handle_command(&self, command: WatcherCommand, debouncer: &mut WatcherHandle) {
+        match command {
+            WatcherCommand::AddNamespace(namespace) => {
+                let is_new = {
+                    let mut namespaces = self.namespaces.lock().unwrap();
+                    if namespaces.contains(&namespace) {
+                        false
+                    } else {
+                        namespaces.push(namespace.clone());
+                        true
+                    }
+                };
+
+                if !is_new {
+                    return;
+                }
+
+                self.watch_namespace_paths(std::slice::from_ref(&namespace), debouncer)
+                    .await;
+            }
+            WatcherCommand::RemoveNamespace(namespace) => {
+                self.namespaces.lock().unwrap().retain(|ns| ns != &namespace);
+
+                let paths = match find_log_files(
+                    &self.directory,
+                    std::slice::from_ref(&namespace),
+                    self.recursive,
+                    &self.filename_patterns,
+                )
+                .await
+                {
+                    Ok(paths) => paths,
+                    Err(error) => {
+                        debug!("Failed to scan namespace {} for removal: {}", namespace, error);
+                        return;
+                    }
+                };
+
+                for path in paths {
+                    let _ = debouncer.unwatch(&path);
+                    self.watched_paths.lock().unwrap().remove(&path);
+
+                    if let Some(event) = self.build_event(
+                        &path,
+                        std::slice::from_ref(&namespace),
+                        LogMetadataWatchEventType::Deleted,
+                    ) {
+                        if !self.send_event(event).await {
+                            debug!("Channel closed from client.");
+                            return;
+                        }
+                    }
+                }
+            }
+            WatcherCommand::Rescan => {
+                let namespaces = self.namespaces_snapshot();
+                self.watch_namespace_paths(&namespaces, debouncer).await;
+            }
+            WatcherCommand::Flush => debouncer.flush(),
+        }
+    }
+
+    // Scans the directory for files matching the given namespaces and adds any that aren't
+    // already being watched. Watching an already-watched path is a harmless no-op error.
+    async fn watch_namespace_paths(&self, namespaces: &[String], debouncer: &mut WatcherHandle) {
+        let paths = match find_log_files(
+            &self.directory,
+            namespaces,
+            self.recursive,
+            &self.filename_patterns,
+        )
+        .await
+        {
+            Ok(paths) => paths,
+            Err(error) => {
+                debug!("Failed to scan directory for namespace update: {}", error);
+                return;
+            }
+        };
+
+        for path in paths {
+            if let Err(error) = debouncer.watch(&path, RecursiveMode::NonRecursive) {
+                debug!(
+                    "Failed to watch {} after namespace update: {}",
+                    path.to_string_lossy(),
+                    error
+                );
+            }
+
+            self.watched_paths.lock().unwrap().insert(path);
+        }
+    }
+
+    // Reconciles the debouncer's watch list and the client's view against the authoritative
+    // current filesystem state. Called after the backend reports that it may have dropped events
+    // (e.g. an inotify queue overflow under heavy churn), at which point incremental events alone
+    // can no longer be trusted to reflect what's actually on disk.
+    async fn resync(&self, debouncer: &mut WatcherHandle) {
+        let namespaces = self.namespaces_snapshot();
+
+        let current_paths: HashSet<PathBuf> = match find_log_files(
+            &self.directory,
+            &namespaces,
+            self.recursive,
+            &self.filename_patterns,
+        )
+        .await
+        {
+            Ok(paths) => paths.into_iter().collect(),
+            Err(error) => {
+                debug!("Failed to resync after watch queue overflow: {}", error);
+                return;
+            }
+        };
+
+        let previous_paths = self.watched_paths.lock().unwrap().clone();
+
+        for path in current_paths.difference(&previous_paths) {
+            if let Err(error) = debouncer.watch(path, RecursiveMode::NonRecursive) {
+                debug!("Failed to watch {} during resync: {}", path.to_string_lossy(), error);
+            }
+
+            if let Some(event) =
+                self.build_event(path, &namespaces, LogMetadataWatchEventType::Added)
+            {
+                if !self.send_event(event).await {
+                    debug!("Channel closed from client.");
+                    return;
+                }
+            }
+        }
+
+        for path in previous_paths.difference(&current_paths) {
+            let _ = debouncer.unwatch(path);
+
+            if let Some(event) =
+                self.build_event(path, &namespaces, LogMetadataWatchEventType::Deleted)
+            {
+                if !self.send_event(event).await {
+                    debug!("Channel closed from client.");
+                    return;
+                }
+            }
+        }
+
+        *self.watched_paths.lock().unwrap() = current_paths;
+    }
+
     // In case of a new log file creation, it adds the path to the notify watcher in order to
     // receive updates for the file in the future. On removal, the path is removed from the watcher
     // accordingly.
-    fn update_watcher<T: Watcher>(
-        &self,
-        watch_event: &LogMetadataWatchEvent,
-        watcher: &mut Debouncer<T, RecommendedCache>,
-    ) {
+    async fn update_watcher(&self, watch_event: &LogMetadataWatchEvent, watcher: &mut WatcherHandle) {
         let Some(event_type) = LogMetadataWatchEventType::from_str(&watch_event.r#type) else {
             return;
         };
 
         if !matches!(
             event_type,
-            LogMetadataWatchEventType::Added | LogMetadataWatchEventType::Deleted
+            LogMetadataWatchEventType::Added
+                | LogMetadataWatchEventType::Deleted
+                | LogMetadataWatchEventType::Rotated
         ) {
             return;
         }
 
-        let Some(file_path) = self.get_file_path(watch_event) else {
+        let Some(file_path) = self.get_file_path(watch_event).await else {
             return;
         };
 
@@ -193,9 +627,19 @@ impl LogMetadataWatcher {
             // Methods watch and unwatch can fail on adding an existing path or on removing a
             // non-existing one. There are no specific actions needed in case this happens.
             LogMetadataWatchEventType::Added => {
+                self.watched_paths.lock().unwrap().insert(file_path.clone());
+                watcher.watch(&file_path, RecursiveMode::NonRecursive)
+            }
+            LogMetadataWatchEventType::Deleted => {
+                self.watched_paths.lock().unwrap().remove(&file_path);
+                watcher.unwatch(&file_path)
+            }
+            // The path kept its name but its inode changed underneath us (or was copytruncated),
+            // so the existing watch may still be bound to the old inode. Re-arm it on the new one.
+            LogMetadataWatchEventType::Rotated => {
+                let _ = watcher.unwatch(&file_path);
                 watcher.watch(&file_path, RecursiveMode::NonRecursive)
             }
-            LogMetadataWatchEventType::Deleted => watcher.unwatch(&file_path),
             LogMetadataWatchEventType::Modified => return,
         };
 
@@ -215,8 +659,10 @@ impl LogMetadataWatcher {
         }
     }
 
-    // Reconstruct the absolut file path from a LogMetadataWatchEvent.
-    fn get_file_path(&self, watch_event: &LogMetadataWatchEvent) -> Option<PathBuf> {
+    // Reconstruct the absolute file path from a LogMetadataWatchEvent. Under a recursive layout
+    // the file may live in a subdirectory the spec doesn't encode, so the directory tree is
+    // searched for a file with the expected name.
+    async fn get_file_path(&self, watch_event: &LogMetadataWatchEvent) -> Option<PathBuf> {
         let file_metadata = watch_event.object.as_ref()?.spec.as_ref()?;
         let filename = format!(
             "{}_{}_{}-{}.log",
@@ -225,7 +671,48 @@ impl LogMetadataWatcher {
             file_metadata.container_name,
             file_metadata.container_id
         );
-        Some(self.directory.join(filename))
+        find_file_path(&self.directory, &filename, self.recursive).await
+    }
+}
+
+// Wraps the concrete debouncer chosen by `WatcherKind` so the rest of the watcher can stay
+// agnostic to which notify backend is in use.
+enum WatcherHandle {
+    Native(Debouncer<RecommendedWatcher, RecommendedCache>),
+    Poll(Debouncer<PollWatcher, RecommendedCache>),
+}
+
+impl WatcherHandle {
+    fn watch(&mut self, path: &Path, mode: RecursiveMode) -> notify::Result<()> {
+        match self {
+            Self::Native(debouncer) => debouncer.watch(path, mode),
+            Self::Poll(debouncer) => debouncer.watch(path, mode),
+        }
+    }
+
+    fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+        match self {
+            Self::Native(debouncer) => debouncer.unwatch(path),
+            Self::Poll(debouncer) => debouncer.unwatch(path),
+        }
+    }
+
+    fn stop(self) {
+        match self {
+            Self::Native(debouncer) => debouncer.stop(),
+            Self::Poll(debouncer) => debouncer.stop(),
+        }
+    }
+
+    // Drains and processes any events the debouncer is currently holding back for
+    // `debounce_interval`, instead of waiting for the timer. The resulting events are delivered
+    // to `event_handler` exactly as a normal timer-driven flush would, so they still go through
+    // `handle_debounced_events` on their way to the client.
+    fn flush(&mut self) {
+        match self {
+            Self::Native(debouncer) => debouncer.flush(),
+            Self::Poll(debouncer) => debouncer.flush(),
+        }
     }
 }
 
@@ -239,6 +726,12 @@ enum WatcherError {
 
     #[error("Log directory not found: {0}")]
     DirNotFound(String),
+
+    /// Internal sentinel: the debouncer reported a dropped/overflowed event batch (an inotify
+    /// queue overflow or the backend's own rescan signal). `listen_for_changes` intercepts this
+    /// and triggers `resync` instead of ever forwarding it to a client.
+    #[error("Watch queue overflowed; a resync is needed")]
+    QueueOverflow,
 }
 
 impl From<WatcherError> for Status {
@@ -249,14 +742,32 @@ impl From<WatcherError> for Status {
             WatcherError::DirNotFound(_) => {
                 Self::new(tonic::Code::NotFound, watcher_error.to_string())
             }
+            WatcherError::QueueOverflow => {
+                Self::new(tonic::Code::Internal, watcher_error.to_string())
+            }
         }
     }
 }
 
-// Helper method to find the log files in a directory that belonging to the specified namespaces.
+// Helper method to find the log files belonging to the specified namespaces under a directory.
+// When `recursive` is set, also descends into subdirectories (following symlinked ones), for
+// layouts that nest log files under per-pod/per-container subdirectories instead of a flat
+// directory. A matched file that is itself a symlink has its resolved target returned alongside
+// the link so the debouncer ends up watching both.
+//
+// Identity (namespace/pod/container) is still read from `filename_patterns` against the bare
+// filename, as it is for the flat layout; this does not parse it from a directory structure like
+// kubelet's real `/var/log/pods/<namespace>_<pod>_<uid>/<container>/<n>.log` tree, since that
+// layout has no container ID anywhere in the path and this service has no other source (e.g. a
+// CRI lookup) to recover one from. Pointing `directory` below `/var/log/pods` itself therefore
+// still requires the `/var/log/containers/*.log` symlink layer to supply identity-bearing
+// filenames; what this enables is descending into *that* tree through the symlinks, and keeping
+// the real rotated-segment paths on the other side of them in sync.
 async fn find_log_files(
     directory: &Path,
     namespaces: &[String],
+    recursive: bool,
+    filename_patterns: &[Regex],
 ) -> Result<Vec<PathBuf>, WatcherError> {
     if !directory.is_dir() {
         return Err(WatcherError::DirNotFound(
@@ -264,73 +775,328 @@ async fn find_log_files(
         ));
     }
 
-    let result = ReadDirStream::new(read_dir(directory).await?)
-        .collect::<Result<Vec<_>, _>>()
-        .await?
-        .into_iter()
-        .filter_map(|file| {
-            let filename = file.file_name();
+    let mut result = Vec::new();
+    let mut dirs_to_scan = VecDeque::from([directory.to_path_buf()]);
+    // Canonical targets of directories already queued, so a symlink cycle (or two links landing
+    // on the same real tree) can't make the walk loop forever.
+    let mut visited_dirs = HashSet::new();
+
+    while let Some(dir) = dirs_to_scan.pop_front() {
+        let mut entries = ReadDirStream::new(read_dir(&dir).await?);
+
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let path = entry.path();
+
+            // `entry.file_type()` reports the entry itself without following symlinks, so a
+            // directory reached only through a symlink (as with kubelet's
+            // /var/log/containers -> /var/log/pods/<ns>_<pod>_<uid>/<container> layout) would
+            // otherwise never be descended into.
+            if recursive && is_dir_following_symlinks(&path).await {
+                if let Ok(canonical) = tokio::fs::canonicalize(&path).await {
+                    if !visited_dirs.insert(canonical) {
+                        continue;
+                    }
+                }
+                dirs_to_scan.push_back(path);
+                continue;
+            }
+
+            let filename = entry.file_name();
             let filename = filename.to_string_lossy();
-            let captures = LOG_FILE_REGEX.captures(&filename)?;
+            let Some(captures) = filename_patterns
+                .iter()
+                .find_map(|pattern| pattern.captures(filename.as_ref()))
+            else {
+                continue;
+            };
 
             if namespaces.is_empty()
                 || namespaces.contains(&captures.name("namespace").unwrap().as_str().to_owned())
             {
-                Some(directory.to_path_buf().join(file.file_name()))
-            } else {
-                None
+                // `path` is frequently a symlink itself (e.g. a /var/log/containers entry
+                // pointing into /var/log/pods); register the resolved target alongside it, or
+                // rotation of the real file underneath could go unnoticed while only the link
+                // is watched.
+                if let Ok(real_path) = tokio::fs::canonicalize(&path).await {
+                    if real_path != path {
+                        result.push(real_path);
+                    }
+                }
+
+                result.push(path);
             }
-        })
-        .collect();
+        }
+    }
 
     Ok(result)
 }
 
+// Resolves symlinks before checking, unlike a `DirEntry`'s own `file_type()`.
+async fn is_dir_following_symlinks(path: &Path) -> bool {
+    tokio::fs::metadata(path)
+        .await
+        .map(|metadata| metadata.is_dir())
+        .unwrap_or(false)
+}
+
+// Searches `directory` (and, if `recursive`, its subdirectories) for a file named `filename`.
+// Used to recover the full path of a file from its spec, since under a recursive layout the
+// subdirectory a file lives under can't be derived from the spec alone.
+async fn find_file_path(directory: &Path, filename: &str, recursive: bool) -> Option<PathBuf> {
+    let flat_path = directory.join(filename);
+    if !recursive {
+        return Some(flat_path);
+    }
+
+    if flat_path.is_file() {
+        return Some(flat_path);
+    }
+
+    let mut dirs_to_scan = VecDeque::from([directory.to_path_buf()]);
+
+    while let Some(dir) = dirs_to_scan.pop_front() {
+        let mut entries = ReadDirStream::new(read_dir(&dir).await.ok()?);
+
+        while let Some(entry) = entries.next().await {
+            let entry = entry.ok()?;
+
+            if entry.file_type().await.ok()?.is_dir() {
+                dirs_to_scan.push_back(entry.path());
+            } else if entry.file_name().to_string_lossy() == filename {
+                return Some(entry.path());
+            }
+        }
+    }
+
+    None
+}
+
+// Per-path rolling blake3 state: the size the hasher has consumed up to, and the hasher itself.
+#[derive(Debug)]
+struct DigestState {
+    size: u64,
+    hasher: blake3::Hasher,
+}
+
+// Updates `path`'s entry in `digest_cache` with any bytes appended since the last call and
+// returns the resulting hex digest. Hashes only the newly appended byte range rather than the
+// whole file; if `path` has shrunk below the previously hashed size (truncation or a
+// rewrite-in-place rotation), the hasher is reset and rehashes from the start.
+fn rolling_digest(
+    digest_cache: &Mutex<HashMap<PathBuf, DigestState>>,
+    path: &Path,
+) -> Option<String> {
+    let size = std::fs::metadata(path).ok()?.size();
+    let mut digest_cache = digest_cache.lock().unwrap();
+    let state = digest_cache.entry(path.to_path_buf()).or_insert_with(|| DigestState {
+        size: 0,
+        hasher: blake3::Hasher::new(),
+    });
+
+    if size < state.size {
+        state.size = 0;
+        state.hasher = blake3::Hasher::new();
+    }
+
+    if size > state.size {
+        let mut file = File::open(path).ok()?;
+        file.seek(SeekFrom::Start(state.size)).ok()?;
+        io::copy(&mut file.take(size - state.size), &mut state.hasher).ok()?;
+        state.size = size;
+    }
+
+    Some(state.hasher.finalize().to_hex().to_string())
+}
+
+// Last observed (device, inode, size) for a logical container identity, used to detect rotation.
+#[derive(Debug, Clone, Copy)]
+struct RotationState {
+    dev: u64,
+    ino: u64,
+    size: u64,
+}
+
+// Detects a kubelet-style log rotation for the container identity (namespace/pod/container
+// triple) that `path` and `size` belong to: either the underlying inode changed while the
+// logical name persisted (rename-based rotation), or the size dropped discontinuously on the
+// same inode (copytruncate-style rotation). Returns the old size to report on a ROTATED event,
+// or None when this isn't a rotation, including the first time this identity is observed.
+fn detect_rotation(
+    rotation_cache: &Mutex<HashMap<(String, String, String), RotationState>>,
+    path: &Path,
+    metadata_spec: &LogMetadataSpec,
+    size: i64,
+) -> Option<i64> {
+    let stat = std::fs::metadata(path).ok()?;
+    let current = RotationState {
+        dev: stat.dev(),
+        ino: stat.ino(),
+        size: size.max(0) as u64,
+    };
+
+    let key = (
+        metadata_spec.namespace.clone(),
+        metadata_spec.pod_name.clone(),
+        metadata_spec.container_name.clone(),
+    );
+
+    let previous = rotation_cache.lock().unwrap().insert(key, current);
+    let previous = previous?;
+
+    (previous.ino != current.ino || current.size < previous.size).then_some(previous.size as i64)
+}
+
 // A DebounceEventResult contains many file events. This method breaks it down and transforms each
 // event to a LogMetadataWatchEvent or to an error in case the debounced events are errors.
 fn handle_debounced_events(
     debounced_event_result: DebounceEventResult,
     namespaces: &[String],
     node_name: &str,
+    digest_cache: &Mutex<HashMap<PathBuf, DigestState>>,
+    last_digests: &HashMap<String, String>,
+    rotation_cache: &Mutex<HashMap<(String, String, String), RotationState>>,
+    retention: &RetentionConfig,
+    filename_patterns: &[Regex],
 ) -> VecDeque<Result<LogMetadataWatchEvent, WatcherError>> {
     let events = match debounced_event_result {
-        Err(errors) => return errors.into_iter().map(|error| Err(error.into())).collect(),
-        Ok(debounced_events) => debounced_events
+        // Some notify backends report a queue overflow as an error rather than an event (e.g.
+        // "IN_Q_OVERFLOW"-style messages from the inotify backend); treat any error mentioning an
+        // overflow as the same resync trigger as an `EventKind::Other` below, and pass the rest
+        // through unchanged.
+        Err(errors) => errors
             .into_iter()
-            .filter(|debounced_event| {
-                matches!(
-                    debounced_event.kind,
-                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
-                )
+            .map(|error| {
+                if error.to_string().to_lowercase().contains("overflow") {
+                    Err(WatcherError::QueueOverflow)
+                } else {
+                    Err(error.into())
+                }
             })
+            .collect(),
+        Ok(debounced_events) => debounced_events
+            .into_iter()
             .filter_map(|debounced_event| {
-                transform_notify_event(&debounced_event.event, namespaces, node_name)
+                match debounced_event.kind {
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                        transform_notify_event(
+                            &debounced_event.event,
+                            namespaces,
+                            node_name,
+                            digest_cache,
+                            last_digests,
+                            rotation_cache,
+                            retention,
+                            filename_patterns,
+                        )
+                    }
+                    // The backend's own signal that it dropped or couldn't reliably deliver
+                    // events (e.g. an inotify queue overflow under heavy churn) rather than a
+                    // real filesystem change for a specific path.
+                    EventKind::Other => Some(Err(WatcherError::QueueOverflow)),
+                    _ => None,
+                }
             })
             .collect(),
     };
 
-    deduplicate_metadata_events(events)
+    coalesce_debounced_events(events)
 }
 
-// Deduplicates a list of metadata events by discarding the duplicate events which are oldest.
-fn deduplicate_metadata_events(
+// Coalesces many raw notify events for the same container_id, observed within one debounce
+// batch, into a single outgoing event. A rapidly-written file otherwise yields several MODIFIED
+// events for the same container, and a create-then-write yields both an ADDED and a MODIFIED;
+// sending all of those to the client is redundant traffic for information a single event already
+// conveys.
+fn coalesce_debounced_events(
     metadata_events: Vec<Result<LogMetadataWatchEvent, WatcherError>>,
 ) -> VecDeque<Result<LogMetadataWatchEvent, WatcherError>> {
-    let mut deduped_events = VecDeque::new();
-    let mut event_index = HashSet::new();
+    // The coalesced event for each container_id seen so far. An IndexMap rather than a HashMap so
+    // that a container's position in the emitted order is fixed by where it was *first* seen,
+    // even as the event stored under it keeps being overwritten by later events for the same
+    // container.
+    let mut by_container: IndexMap<String, LogMetadataWatchEvent> = IndexMap::new();
+
+    for result in &metadata_events {
+        let Ok(event) = result else { continue };
+        let container_id = container_id_of(event);
+
+        match by_container.get_mut(&container_id) {
+            Some(merged) => {
+                merged.r#type = coalesce_event_type(&merged.r#type, &event.r#type).to_owned();
+                // The object (including file_info) always comes from the most recently
+                // transformed event, so the surviving event's size reflects the final state of
+                // the batch rather than an intermediate one.
+                merged.object = event.object.clone();
+                merged.previous_size = event.previous_size;
+            }
+            None => {
+                by_container.insert(container_id, event.clone());
+            }
+        }
+    }
+
+    let mut emitted_containers = HashSet::new();
+    let mut coalesced = VecDeque::new();
 
-    for result in metadata_events.into_iter().rev() {
-        match &result {
-            Err(_) => deduped_events.push_front(result),
+    for result in metadata_events {
+        match result {
+            Err(error) => coalesced.push_back(Err(error)),
             Ok(event) => {
-                if event_index.insert(event.clone()) {
-                    deduped_events.push_front(result);
+                let container_id = container_id_of(&event);
+                if emitted_containers.insert(container_id.clone()) {
+                    // `by_container` was populated from this same list above, so the lookup
+                    // always succeeds.
+                    coalesced.push_back(Ok(by_container.shift_remove(&container_id).unwrap()));
                 }
             }
         }
     }
 
-    deduped_events
+    coalesced
+}
+
+fn container_id_of(event: &LogMetadataWatchEvent) -> String {
+    event
+        .object
+        .as_ref()
+        .map(|object| object.id.clone())
+        .unwrap_or_default()
+}
+
+// Applies the precedence rules for collapsing two events observed for the same container_id
+// within one debounce batch into the single type that should be reported for it.
+fn coalesce_event_type(existing: &str, incoming: &str) -> &'static str {
+    let (existing, incoming) = match (
+        LogMetadataWatchEventType::from_str(existing),
+        LogMetadataWatchEventType::from_str(incoming),
+    ) {
+        (Some(existing), Some(incoming)) => (existing, incoming),
+        _ => return LogMetadataWatchEventType::Modified.as_str(),
+    };
+
+    match (existing, incoming) {
+        // A create that was immediately written to is, from the client's perspective, still just
+        // a new file appearing - report it as ADDED rather than ADDED followed by MODIFIED.
+        (LogMetadataWatchEventType::Added, LogMetadataWatchEventType::Modified) => {
+            LogMetadataWatchEventType::Added.as_str()
+        }
+        // Whatever came before, the file is gone now.
+        (_, LogMetadataWatchEventType::Deleted) => LogMetadataWatchEventType::Deleted.as_str(),
+        // A path deleted and then recreated within the same debounce window is reported as
+        // MODIFIED: the client already knows about this container_id from before the window
+        // started, so an ADDED here would be misleading.
+        (LogMetadataWatchEventType::Deleted, LogMetadataWatchEventType::Added) => {
+            LogMetadataWatchEventType::Modified.as_str()
+        }
+        // A rewritten-in-place file that's then atomically replaced (e.g. a rename-based rotation
+        // racing the tail end of a write) reports the same container_id as both MODIFIED and
+        // ADDED within one batch; ADDED wins since that's the backend's final word on the path.
+        (LogMetadataWatchEventType::Modified, LogMetadataWatchEventType::Added) => {
+            LogMetadataWatchEventType::Added.as_str()
+        }
+        (_, incoming) => incoming.as_str(),
+    }
 }
 
 // Transform a single Event to a LogMetadataWatchEvent. Fails in cases there is an IO error when
@@ -339,6 +1105,11 @@ fn transform_notify_event(
     event: &Event,
     namespaces: &[String],
     node_name: &str,
+    digest_cache: &Mutex<HashMap<PathBuf, DigestState>>,
+    last_digests: &HashMap<String, String>,
+    rotation_cache: &Mutex<HashMap<(String, String, String), RotationState>>,
+    retention: &RetentionConfig,
+    filename_patterns: &[Regex],
 ) -> Option<Result<LogMetadataWatchEvent, WatcherError>> {
     let mut event_type = match event.kind {
         EventKind::Modify(_) => LogMetadataWatchEventType::Modified,
@@ -349,7 +1120,16 @@ fn transform_notify_event(
 
     let path = event.paths.first()?;
 
-    let metadata_spec = LogMetadataImpl::get_log_metadata_spec(path, namespaces, node_name)?;
+    // Check the retention policy before inspecting the file any further: if it's grown past
+    // `max_size` this renames it aside exactly as an external logrotate would, so the
+    // inode-comparison in `detect_rotation` below reports the same ROTATED event it would for a
+    // rotation driven by an outside process.
+    if !matches!(event_type, LogMetadataWatchEventType::Deleted) {
+        let _ = retention::rotate_if_needed(retention, path);
+    }
+
+    let metadata_spec =
+        LogMetadataImpl::get_log_metadata_spec(path, filename_patterns, namespaces, node_name)?;
     let file_info = LogMetadataImpl::get_file_info(path);
 
     // In case the file doesn't exist turn the event into a deletion event, otherwise propagete the
@@ -362,16 +1142,53 @@ fn transform_notify_event(
         }
     }
 
+    if matches!(event_type, LogMetadataWatchEventType::Deleted) {
+        digest_cache.lock().unwrap().remove(path);
+    }
+
+    let mut file_info = file_info.unwrap_or(LogMetadataFileInfo {
+        size: 0,
+        last_modified_at: None,
+        digest: None,
+        media_type: None,
+        inode: 0,
+    });
+
+    let mut previous_size = None;
+
+    if !matches!(event_type, LogMetadataWatchEventType::Deleted) {
+        if let Some(old_size) = detect_rotation(rotation_cache, path, &metadata_spec, file_info.size)
+        {
+            digest_cache.lock().unwrap().remove(path);
+            event_type = LogMetadataWatchEventType::Rotated;
+            previous_size = Some(old_size);
+        }
+
+        // A gzip-compressed segment is already fully hashed over its decompressed content by
+        // get_file_info; the rolling cache only makes sense for a plaintext file that's growing
+        // in place.
+        if file_info.media_type.as_deref() != Some(GZIP_MEDIA_TYPE) {
+            file_info.digest = rolling_digest(digest_cache, path);
+        }
+
+        // An If-None-Match-style short-circuit: if the caller already saw this exact digest for
+        // this container before (re)connecting, a MODIFIED event carrying it back is pure noise.
+        if matches!(event_type, LogMetadataWatchEventType::Modified)
+            && file_info.digest.is_some()
+            && last_digests.get(&metadata_spec.container_id) == file_info.digest.as_ref()
+        {
+            return None;
+        }
+    }
+
     Some(Ok(LogMetadataWatchEvent {
         r#type: event_type.as_str().to_owned(),
         object: Some(LogMetadata {
             id: metadata_spec.container_id.clone(),
             spec: Some(metadata_spec),
-            file_info: Some(file_info.unwrap_or(LogMetadataFileInfo {
-                size: 0,
-                last_modified_at: None,
-            })),
+            file_info: Some(file_info),
         }),
+        previous_size,
     }))
 }
 
@@ -380,6 +1197,10 @@ enum LogMetadataWatchEventType {
     Added,
     Modified,
     Deleted,
+    /// The path kept its logical container identity but its underlying file was replaced: either
+    /// the inode changed (rename-based rotation) or the size dropped discontinuously on the same
+    /// inode (copytruncate-style rotation).
+    Rotated,
 }
 
 impl LogMetadataWatchEventType {
@@ -388,6 +1209,7 @@ impl LogMetadataWatchEventType {
             "ADDED" => Some(Self::Added),
             "MODIFIED" => Some(Self::Modified),
             "DELETED" => Some(Self::Deleted),
+            "ROTATED" => Some(Self::Rotated),
             _ => None,
         }
     }
@@ -397,6 +1219,7 @@ impl LogMetadataWatchEventType {
             Self::Added => "ADDED",
             Self::Modified => "MODIFIED",
             Self::Deleted => "DELETED",
+            Self::Rotated => "ROTATED",
         }
     }
 }
@@ -412,12 +1235,11 @@ mod test {
     use crate::log_metadata::test::create_test_file;
 
     use super::*;
-    use notify::{PollWatcher, RecommendedWatcher};
     use serial_test::{parallel, serial};
     use tokio::{
         sync::{broadcast, mpsc::error::TryRecvError},
         task,
-        time::sleep,
+        time::{sleep, timeout},
     };
 
     #[tokio::test]
@@ -433,15 +1255,17 @@ mod test {
             namespaces,
             term_tx.clone(),
             "The node name".to_owned(),
+            WatcherKind::Poll(Duration::from_millis(100)),
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
         );
 
-        task::spawn(async move {
-            log_metadata_watcher
-                .watch::<PollWatcher>(Some(
-                    notify::Config::default().with_poll_interval(Duration::from_millis(100)),
-                ))
-                .await
-        });
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
 
         // Wait until the watcher has started listening for changes
         while term_tx.receiver_count() != 2 {
@@ -499,6 +1323,129 @@ mod test {
         assert!(matches!(result, Err(TryRecvError::Empty)));
     }
 
+    #[tokio::test]
+    #[parallel]
+    async fn test_existing_files_are_snapshotted_on_watch_start() {
+        let file = create_test_file("pod-name_snapshot-namespace_container-name-containerid", 4);
+        let namespaces = vec!["snapshot-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // The file existed before the watch started, so it should be reported as ADDED.
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        verify_event(
+            event,
+            "ADDED",
+            "containerid",
+            "The node name",
+            "snapshot-namespace",
+            "pod-name",
+            "container-name",
+            Some(4),
+        );
+    }
+
+    // Regression test: find_log_files previously matched every filename against the hardcoded
+    // LOG_FILE_REGEX instead of the configured filename_patterns, so a file matching only a
+    // custom pattern was never included in the startup snapshot or individually watched.
+    #[tokio::test]
+    #[parallel]
+    async fn test_existing_files_matching_custom_pattern_are_snapshotted_on_watch_start() {
+        let custom_pattern = Regex::new(concat!(
+            r"^custom-(?P<namespace>[^-]+)-(?P<pod_name>[^-]+)-(?P<container_name>[^-]+)-",
+            r"(?P<container_id>[^-]+)\.log$",
+        ))
+        .unwrap();
+
+        let file =
+            create_test_file("custom-snapshotnamespace-podname-containername-containerid", 4);
+        let namespaces = vec!["snapshotnamespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![custom_pattern]),
+        );
+
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // The file only matches the custom pattern, not LOG_FILE_REGEX, so it should still be
+        // reported as ADDED in the startup snapshot.
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        verify_event(
+            event,
+            "ADDED",
+            "containerid",
+            "The node name",
+            "snapshotnamespace",
+            "podname",
+            "containername",
+            Some(4),
+        );
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_build_event_skips_added_for_vanished_file() {
+        // Regression test: a file that existed at discovery time but is gone by the time
+        // build_event inspects it (e.g. rotated away mid-scan) must not produce a phantom ADDED
+        // event with fabricated zero-size file_info.
+        let namespaces = vec!["vanished-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+
+        let (log_metadata_watcher, _log_metadata_rx) = LogMetadataWatcher::new(
+            PathBuf::from("/tmp"),
+            namespaces.clone(),
+            term_tx,
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let path =
+            PathBuf::from("/tmp/pod-name_vanished-namespace_container-name-containerid.log");
+
+        let event = log_metadata_watcher.build_event(
+            &path,
+            &namespaces,
+            LogMetadataWatchEventType::Added,
+        );
+        assert!(event.is_none());
+    }
+
     #[tokio::test]
     #[parallel]
     async fn test_error_is_returned_on_unknown_directory() {
@@ -511,9 +1458,17 @@ mod test {
             namespaces,
             term_tx.clone(),
             "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
         );
 
-        task::spawn(async move { log_metadata_watcher.watch::<RecommendedWatcher>(None).await });
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
 
         let result = log_metadata_rx.recv().await.unwrap();
         assert!(matches!(result, Err(_)));
@@ -536,10 +1491,18 @@ mod test {
             namespaces,
             term_tx.clone(),
             "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
         );
 
         // File deletions return errors when using PollWatcher so we use RecommendedWatcher
-        task::spawn(async move { log_metadata_watcher.watch::<RecommendedWatcher>(None).await });
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
 
         // Wait until the watcher has started listening for changes
         while term_tx.receiver_count() != 2 {
@@ -563,6 +1526,54 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    #[parallel]
+    async fn test_kind_filter_drops_non_matching_events() {
+        let file = create_test_file("pod-name_filterkind-namespace_container-name-containerid", 4);
+        let namespaces = vec!["filterkind-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::from(["DELETED".to_owned()]),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // Wait until the watcher has started listening for changes
+        while term_tx.receiver_count() != 2 {
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        // The startup snapshot would normally send an ADDED event for the file that already
+        // exists, but the filter only admits DELETED; deleting the file should be the first (and
+        // only) event the client ever sees.
+        let _ = file.close();
+
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        verify_event(
+            event,
+            "DELETED",
+            "containerid",
+            "The node name",
+            "filterkind-namespace",
+            "pod-name",
+            "container-name",
+            None,
+        );
+    }
+
     #[tokio::test]
     #[cfg(not(target_os = "macos"))]
     #[parallel]
@@ -577,10 +1588,18 @@ mod test {
             namespaces,
             term_tx.clone(),
             "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
         );
 
         // Start the watcher and give it some time to execute before creating events.
-        task::spawn(async move { log_metadata_watcher.watch::<RecommendedWatcher>(None).await });
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
 
         // Wait until the watcher has started listening for changes
         while term_tx.receiver_count() != 2 {
@@ -636,6 +1655,275 @@ mod test {
         let _ = remove_file(&new_path);
     }
 
+    #[tokio::test]
+    #[parallel]
+    async fn test_rotated_event_is_generated_on_copytruncate() {
+        use std::{fs::OpenOptions, io::Write as _};
+
+        let file = create_test_file("pod-name_rotate-namespace_container-name-containerid", 4);
+        let namespaces = vec!["rotate-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // Consume the initial ADDED snapshot event for the pre-existing file.
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "ADDED");
+
+        // Grow the file. This is the first live event for this container identity, so it seeds
+        // the rotation tracker's baseline rather than being reported as a rotation.
+        let mut file_handle = OpenOptions::new().append(true).open(file.path()).unwrap();
+        file_handle.write_all(&vec![1; 4]).unwrap();
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "MODIFIED");
+        assert_eq!(event.object.as_ref().unwrap().file_info.as_ref().unwrap().size, 8);
+
+        // Truncate the file and write fewer bytes than were previously recorded, on the same
+        // inode: a copytruncate-style rotation.
+        let mut file_handle = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(file.path())
+            .unwrap();
+        file_handle.write_all(&vec![2; 2]).unwrap();
+
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "ROTATED");
+        assert_eq!(event.previous_size, Some(8));
+        assert_eq!(event.object.as_ref().unwrap().file_info.as_ref().unwrap().size, 2);
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_retention_triggered_rotation_is_reported_as_rotated() {
+        use std::{fs::OpenOptions, io::Write as _};
+
+        let file = create_test_file("pod-name_retain-namespace_container-name-containerid", 4);
+        let namespaces = vec!["retain-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig {
+                max_size: Some(4),
+                max_files: 2,
+            },
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // Consume the initial ADDED snapshot event for the pre-existing file.
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "ADDED");
+
+        // Grow the file past max_size. The retention check runs before the event is reported, so
+        // the write that pushes it over the limit is the same one that triggers the rotation: the
+        // old contents land in `container.log.1` and the watched path is replaced by an empty
+        // file, indistinguishable from a rotation an external process would have performed.
+        let mut file_handle = OpenOptions::new().append(true).open(file.path()).unwrap();
+        file_handle.write_all(&vec![1; 4]).unwrap();
+
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "ROTATED");
+        assert_eq!(event.object.as_ref().unwrap().file_info.as_ref().unwrap().size, 0);
+
+        let mut rotated_path = file.path().to_owned();
+        rotated_path.set_extension("log.1");
+        assert_eq!(std::fs::read(&rotated_path).unwrap(), vec![1; 4]);
+        let _ = std::fs::remove_file(&rotated_path);
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_remove_namespace_command_emits_deleted_events() {
+        let file = create_test_file("pod-name_remove-namespace_container-name-containerid", 4);
+        let namespaces = vec!["remove-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // Consume the initial ADDED snapshot event for the pre-existing file.
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "ADDED");
+
+        // Removing the namespace should emit a synthetic DELETED event for its file.
+        command_tx
+            .send(WatcherCommand::RemoveNamespace("remove-namespace".into()))
+            .await
+            .unwrap();
+
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        verify_event(
+            event,
+            "DELETED",
+            "containerid",
+            "The node name",
+            "remove-namespace",
+            "pod-name",
+            "container-name",
+            Some(4),
+        );
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_flush_command_delivers_pending_event_before_debounce_expires() {
+        use std::{fs::OpenOptions, io::Write as _};
+
+        let file = create_test_file("pod-name_flush-namespace_container-name-containerid", 4);
+        let namespaces = vec!["flush-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = file.path().parent().unwrap().to_owned();
+
+        // A debounce window much longer than this test's own timeout below, so the MODIFIED
+        // event can only arrive because of the flush, not because the timer happened to fire.
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx.clone(),
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(60),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
+
+        // Consume the initial ADDED snapshot event for the pre-existing file.
+        let event = log_metadata_rx.recv().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "ADDED");
+
+        let mut file_handle = OpenOptions::new().append(true).open(file.path()).unwrap();
+        file_handle.write_all(&vec![1; 4]).unwrap();
+
+        command_tx.send(WatcherCommand::Flush).await.unwrap();
+
+        let event = timeout(Duration::from_secs(10), log_metadata_rx.recv())
+            .await
+            .expect("Flush should deliver the pending event long before the 60s debounce window")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(event.r#type, "MODIFIED");
+        assert_eq!(event.object.as_ref().unwrap().file_info.as_ref().unwrap().size, 8);
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_resync_reconciles_after_simulated_queue_overflow() {
+        let existing_file =
+            create_test_file("pod-name_resync-namespace_container-name-existingid", 4);
+        let namespaces = vec!["resync-namespace".into()];
+        let (term_tx, _term_rx) = broadcast::channel(1);
+        let logs_dir = existing_file.path().parent().unwrap().to_owned();
+
+        let (log_metadata_watcher, mut log_metadata_rx) = LogMetadataWatcher::new(
+            logs_dir,
+            namespaces,
+            term_tx,
+            "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
+        );
+
+        let (internal_tx, _internal_rx) = channel(10);
+        let (mut debouncer, _existing_paths) = log_metadata_watcher
+            .setup_notify_watcher(internal_tx)
+            .await
+            .unwrap();
+
+        // Simulate a queue overflow that dropped both of these changes: the existing file
+        // vanishes and a new one appears, but the debouncer never reports either.
+        let _ = existing_file.close();
+        let new_file = create_test_file("pod-name_resync-namespace_container-name-newid", 8);
+
+        log_metadata_watcher.resync(&mut debouncer).await;
+
+        let mut events = Vec::new();
+        for _ in 0..2 {
+            events.push(log_metadata_rx.recv().await.unwrap().unwrap());
+        }
+
+        let deleted = events.iter().find(|event| event.r#type == "DELETED").unwrap();
+        verify_event(
+            deleted.clone(),
+            "DELETED",
+            "existingid",
+            "The node name",
+            "resync-namespace",
+            "pod-name",
+            "container-name",
+            None,
+        );
+
+        let added = events.iter().find(|event| event.r#type == "ADDED").unwrap();
+        verify_event(
+            added.clone(),
+            "ADDED",
+            "newid",
+            "The node name",
+            "resync-namespace",
+            "pod-name",
+            "container-name",
+            Some(8),
+        );
+
+        drop(new_file);
+    }
+
     #[tokio::test]
     #[parallel]
     async fn test_sends_unavailable_on_termination_signal() {
@@ -650,10 +1938,18 @@ mod test {
             namespaces,
             term_tx.clone(),
             "The node name".to_owned(),
+            WatcherKind::Native,
+            Duration::from_secs(2),
+            false,
+            HashMap::new(),
+            RetentionConfig::disabled(),
+            HashSet::new(),
+            Arc::new(vec![LOG_FILE_REGEX.clone()]),
         );
 
         // Start the watcher in the background.
-        task::spawn(async move { log_metadata_watcher.watch::<RecommendedWatcher>(None).await });
+        let (_command_tx, command_rx) = channel(1);
+        task::spawn(async move { log_metadata_watcher.watch(command_rx).await });
 
         // Wait until the watcher has subscribed to the termination channel.
         while term_tx.receiver_count() != 2 {
@@ -673,6 +1969,107 @@ mod test {
         assert_eq!(status.message(), "Server is shutting down");
     }
 
+    fn make_event(container_id: &str, event_type: &str, size: i64) -> LogMetadataWatchEvent {
+        LogMetadataWatchEvent {
+            r#type: event_type.to_owned(),
+            object: Some(LogMetadata {
+                id: container_id.to_owned(),
+                spec: None,
+                file_info: Some(LogMetadataFileInfo {
+                    size,
+                    last_modified_at: None,
+                    digest: None,
+                    media_type: None,
+                    inode: 0,
+                }),
+            }),
+            previous_size: None,
+        }
+    }
+
+    #[test]
+    fn test_coalesce_event_type_precedence() {
+        assert_eq!(coalesce_event_type("ADDED", "MODIFIED"), "ADDED");
+        assert_eq!(coalesce_event_type("MODIFIED", "DELETED"), "DELETED");
+        assert_eq!(coalesce_event_type("ADDED", "DELETED"), "DELETED");
+        assert_eq!(coalesce_event_type("DELETED", "ADDED"), "MODIFIED");
+        assert_eq!(coalesce_event_type("MODIFIED", "MODIFIED"), "MODIFIED");
+        assert_eq!(coalesce_event_type("MODIFIED", "ADDED"), "ADDED");
+    }
+
+    #[test]
+    fn test_coalesce_debounced_events_collapses_per_container() {
+        let events = vec![
+            Ok(make_event("container-a", "ADDED", 0)),
+            Ok(make_event("container-b", "MODIFIED", 10)),
+            Ok(make_event("container-a", "MODIFIED", 4)),
+            Ok(make_event("container-a", "MODIFIED", 8)),
+        ];
+
+        let coalesced: Vec<_> = coalesce_debounced_events(events).into_iter().collect();
+
+        // container-a was first seen before container-b, so it keeps that position even though
+        // its final event arrived last.
+        assert_eq!(coalesced.len(), 2);
+
+        let container_a = coalesced[0].as_ref().unwrap();
+        assert_eq!(container_a.object.as_ref().unwrap().id, "container-a");
+        assert_eq!(container_a.r#type, "ADDED");
+        assert_eq!(container_a.object.as_ref().unwrap().file_info.as_ref().unwrap().size, 8);
+
+        let container_b = coalesced[1].as_ref().unwrap();
+        assert_eq!(container_b.object.as_ref().unwrap().id, "container-b");
+        assert_eq!(container_b.r#type, "MODIFIED");
+    }
+
+    #[test]
+    fn test_coalesce_debounced_events_preserves_errors() {
+        let events = vec![
+            Ok(make_event("container-a", "ADDED", 0)),
+            Err(WatcherError::QueueOverflow),
+            Ok(make_event("container-a", "MODIFIED", 4)),
+        ];
+
+        let coalesced: Vec<_> = coalesce_debounced_events(events).into_iter().collect();
+
+        assert_eq!(coalesced.len(), 2);
+        assert!(coalesced[0].is_ok());
+        assert!(matches!(coalesced[1], Err(WatcherError::QueueOverflow)));
+    }
+
+    #[tokio::test]
+    #[parallel]
+    async fn test_find_log_files_follows_symlinked_subdirectories() {
+        let target_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let real_file = target_dir
+            .path()
+            .join("pod-name_symlink-namespace_container-name-containerid.log");
+        std::fs::write(&real_file, vec![0; 4]).expect("Failed to write test file");
+
+        let watch_root = tempfile::tempdir().expect("Failed to create temp dir");
+        let linked_dir = watch_root.path().join("linked");
+        std::os::unix::fs::symlink(target_dir.path(), &linked_dir)
+            .expect("Failed to create symlink");
+
+        let namespaces = vec!["symlink-namespace".to_owned()];
+        let found = find_log_files(
+            watch_root.path(),
+            &namespaces,
+            true,
+            &[LOG_FILE_REGEX.clone()],
+        )
+        .await
+        .expect("find_log_files failed");
+
+        let real_path = tokio::fs::canonicalize(&real_file)
+            .await
+            .expect("Failed to canonicalize real file");
+        let linked_path = linked_dir.join("pod-name_symlink-namespace_container-name-containerid.log");
+
+        assert!(found.contains(&real_path));
+        assert!(found.contains(&linked_path));
+    }
+
     fn verify_event(
         event: LogMetadataWatchEvent,
         event_type: &str,
@@ -702,6 +2099,9 @@ mod test {
                 Some(LogMetadataFileInfo {
                     size: 0,
                     last_modified_at: None,
+                    digest: None,
+                    media_type: None,
+                    inode: 0,
                 })
             );
         }
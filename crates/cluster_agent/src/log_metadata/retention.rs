@@ -0,0 +1,218 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Size-based retention policy for log files the agent owns and rotates itself, modeled on a
+/// classic rotate-by-size logrotate scheme: once a tracked file exceeds `max_size`, it's renamed
+/// aside and kept for up to `max_files` generations before the oldest is dropped.
+///
+/// Fully opt-in: `max_size: None` (the default) disables rotation entirely, leaving files to grow
+/// unbounded exactly as before this policy existed.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionConfig {
+    pub max_size: Option<u64>,
+    pub max_files: u32,
+}
+
+impl RetentionConfig {
+    pub const fn disabled() -> Self {
+        Self {
+            max_size: None,
+            max_files: 0,
+        }
+    }
+}
+
+/// The numbered backup path for the `n`th-oldest prior generation of `path`: `path.1` is the most
+/// recently rotated-out generation, `path.<max_files>` the oldest one still kept.
+fn generation_path(path: &Path, n: u32) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Checks `path` against `config` and, if it has grown past `max_size`, performs a rename-based
+/// rotation: shifts `path.1..path.max_files-1` up by one generation, drops the generation that
+/// falls off the end of `max_files`, renames `path` to `path.1`, and recreates an empty `path` in
+/// its place.
+///
+/// This is the same dance an external `logrotate` would perform, so the caller's existing
+/// rename-based rotation detection (see `detect_rotation` in the parent module) picks up the
+/// result exactly as it would for rotation driven by an outside process: a watch event for
+/// `path` is indistinguishable from one caused by this rotation.
+///
+/// Returns `true` if a rotation happened. Dropping the oldest generation beyond `max_files` is
+/// bookkeeping cleanup only: that numbered backup was never surfaced through `LogMetadataService`
+/// (it doesn't match `LOG_FILE_REGEX`), so no watch event is owed for it.
+pub fn rotate_if_needed(config: &RetentionConfig, path: &Path) -> io::Result<bool> {
+    let Some(max_size) = config.max_size else {
+        return Ok(false);
+    };
+
+    if config.max_files == 0 {
+        return Ok(false);
+    }
+
+    let size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(error) => return Err(error),
+    };
+
+    if size <= max_size {
+        return Ok(false);
+    }
+
+    let oldest = generation_path(path, config.max_files);
+    if oldest.is_file() {
+        fs::remove_file(&oldest)?;
+    }
+
+    for generation in (1..config.max_files).rev() {
+        let from = generation_path(path, generation);
+        if from.is_file() {
+            fs::rename(&from, generation_path(path, generation + 1))?;
+        }
+    }
+
+    fs::rename(path, generation_path(path, 1))?;
+    File::create(path)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_disabled_by_default_leaves_file_untouched() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("container.log");
+        fs::write(&path, vec![0; 16]).unwrap();
+
+        let rotated = rotate_if_needed(&RetentionConfig::disabled(), &path).unwrap();
+
+        assert!(!rotated);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_rotates_once_max_size_exceeded() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("container.log");
+        fs::write(&path, vec![0; 16]).unwrap();
+
+        let config = RetentionConfig {
+            max_size: Some(8),
+            max_files: 2,
+        };
+
+        let rotated = rotate_if_needed(&config, &path).unwrap();
+
+        assert!(rotated);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        assert_eq!(fs::metadata(dir.path().join("container.log.1")).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_below_max_size_is_not_rotated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("container.log");
+        fs::write(&path, vec![0; 4]).unwrap();
+
+        let config = RetentionConfig {
+            max_size: Some(8),
+            max_files: 2,
+        };
+
+        let rotated = rotate_if_needed(&config, &path).unwrap();
+
+        assert!(!rotated);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_shifts_generations_and_drops_the_oldest() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("container.log");
+        fs::write(&path, vec![0; 16]).unwrap();
+        fs::write(dir.path().join("container.log.1"), b"generation 1").unwrap();
+        fs::write(dir.path().join("container.log.2"), b"generation 2").unwrap();
+
+        let config = RetentionConfig {
+            max_size: Some(8),
+            max_files: 2,
+        };
+
+        let rotated = rotate_if_needed(&config, &path).unwrap();
+
+        assert!(rotated);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        assert_eq!(
+            fs::read(dir.path().join("container.log.1")).unwrap(),
+            vec![0; 16]
+        );
+        assert_eq!(
+            fs::read(dir.path().join("container.log.2")).unwrap(),
+            b"generation 1"
+        );
+        // Generation 2 ("generation 2") fell off the end of max_files and was dropped rather
+        // than shifted to generation 3.
+        assert!(!dir.path().join("container.log.3").exists());
+    }
+
+    #[test]
+    fn test_missing_file_is_not_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("gone.log");
+
+        let config = RetentionConfig {
+            max_size: Some(8),
+            max_files: 2,
+        };
+
+        let rotated = rotate_if_needed(&config, &path).unwrap();
+
+        assert!(!rotated);
+    }
+
+    #[test]
+    fn test_max_files_zero_disables_rotation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("container.log");
+        fs::write(&path, vec![0; 16]).unwrap();
+
+        let config = RetentionConfig {
+            max_size: Some(8),
+            max_files: 0,
+        };
+
+        let rotated = rotate_if_needed(&config, &path).unwrap();
+
+        assert!(!rotated);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_write_after_rotation_is_unaffected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("container.log");
+        fs::write(&path, vec![0; 16]).unwrap();
+
+        let config = RetentionConfig {
+            max_size: Some(8),
+            max_files: 1,
+        };
+
+        rotate_if_needed(&config, &path).unwrap();
+
+        let mut file = File::options().append(true).open(&path).unwrap();
+        file.write_all(b"fresh").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"fresh");
+    }
+}
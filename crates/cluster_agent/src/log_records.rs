@@ -1,27 +1,78 @@
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use arc_swap::ArcSwap;
 use chrono::{DateTime, Utc};
 use tokio::sync::broadcast::Sender;
 use tokio::sync::mpsc::{self};
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 use types::cluster_agent::log_records_service_server::LogRecordsService;
-use types::cluster_agent::{LogRecord, LogRecordsStreamRequest};
+use types::cluster_agent::{
+    Capabilities, CapabilitiesRequest, LogRecord, LogRecordBucket, LogRecordsAggregateResponse,
+    LogRecordsStreamRequest,
+};
 
+use rgkl::util::format::FileFormat;
+use rgkl::util::matcher::GrepSpec;
+use rgkl::util::writer::MalformedLinePolicy;
 use rgkl::{stream_backward, stream_forward};
 
 use tonic::{Request, Response, Status};
 
+/// Capability tokens this agent build supports, advertised by `GetCapabilities` and checked
+/// against `LogRecordsStreamRequest.required_capabilities` so a client relying on one this build
+/// lacks gets a clear `UNIMPLEMENTED` error instead of a silently degraded stream.
+const SUPPORTED_FEATURES: &[&str] = &["grep", "follow", "json-records"];
+
+/// Builds a [`GrepSpec`] from a request's `include_patterns`/`exclude_patterns`, or `None` when
+/// both are empty so the caller falls back to the single-pattern `grep` field instead.
+fn grep_spec_from_request(request: &LogRecordsStreamRequest) -> Option<GrepSpec> {
+    if request.include_patterns.is_empty() && request.exclude_patterns.is_empty() {
+        return None;
+    }
+
+    Some(GrepSpec::new(
+        request.include_patterns.clone(),
+        request.exclude_patterns.clone(),
+    ))
+}
+
+/// Returns an `UNIMPLEMENTED` error naming the first capability in `required` that isn't in
+/// [`SUPPORTED_FEATURES`], or `Ok(())` if every one is supported.
+fn check_required_capabilities(required: &[String]) -> Result<(), Status> {
+    if let Some(unsupported) = required
+        .iter()
+        .find(|capability| !SUPPORTED_FEATURES.contains(&capability.as_str()))
+    {
+        return Err(Status::new(
+            tonic::Code::Unimplemented,
+            format!("capability not supported by this agent build: {unsupported}"),
+        ));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct LogRecordsImpl {
-    logs_dir: PathBuf,
+    /// Swapped in place by `apply_reloadable_config` in `main` when `container-logs-dir` changes
+    /// in a reloaded config, so a stream request arriving after the reload resolves against the
+    /// new directory without restarting the agent. Streams already in flight hold their own
+    /// resolved `file_path` and are unaffected by a later swap.
+    logs_dir: Arc<ArcSwap<PathBuf>>,
     term_tx: Sender<()>,
     task_tracker: TaskTracker,
 }
 
 impl LogRecordsImpl {
-    pub const fn new(logs_dir: PathBuf, term_tx: Sender<()>, task_tracker: TaskTracker) -> Self {
+    pub fn new(
+        logs_dir: Arc<ArcSwap<PathBuf>>,
+        term_tx: Sender<()>,
+        task_tracker: TaskTracker,
+    ) -> Self {
         Self {
             logs_dir,
             term_tx,
@@ -35,7 +86,7 @@ impl LogRecordsImpl {
             None => &request.container_id,
         };
 
-        let path = self.logs_dir.join(format!(
+        let path = self.logs_dir.load_full().join(format!(
             "{}_{}_{}-{}.log",
             &request.pod_name, &request.namespace, &request.container_name, container_id
         ));
@@ -63,9 +114,13 @@ impl LogRecordsService for LogRecordsImpl {
         request: Request<LogRecordsStreamRequest>,
     ) -> Result<Response<Self::StreamBackwardStream>, Status> {
         let request = request.into_inner();
+        check_required_capabilities(&request.required_capabilities)?;
         let file_path = self.get_log_filename(&request).map_err(|status| *status)?;
         let (tx, rx) = mpsc::channel(100);
         let term_tx = self.term_tx.clone();
+        let compression = request.compression();
+        let forced_format: Option<FileFormat> = request.forced_format().into();
+        let grep_spec = grep_spec_from_request(&request);
 
         self.task_tracker.spawn(async move {
             stream_backward::stream_backward(
@@ -77,6 +132,13 @@ impl LogRecordsService for LogRecordsImpl {
                 } else {
                     Some(&request.grep)
                 },
+                grep_spec.as_ref(),
+                request.min_severity(),
+                forced_format,
+                compression,
+                // Not yet exposed on the request; malformed lines are dropped with a tracing
+                // warning rather than surfaced to the client or aborting the stream.
+                MalformedLinePolicy::Skip,
                 term_tx,
                 tx,
             )
@@ -92,13 +154,28 @@ impl LogRecordsService for LogRecordsImpl {
         request: Request<LogRecordsStreamRequest>,
     ) -> Result<Response<Self::StreamForwardStream>, Status> {
         let request = request.into_inner();
+        check_required_capabilities(&request.required_capabilities)?;
         let file_path = self.get_log_filename(&request).map_err(|status| *status)?;
 
         let (tx, rx) = mpsc::channel(100);
-        let term_tx = self.term_tx.clone();
+        let task_tracker = self.task_tracker.clone();
+        let tx_for_panic = tx.clone();
+        let forced_format: Option<FileFormat> = request.forced_format().into();
+        let grep_spec = grep_spec_from_request(&request);
 
-        self.task_tracker.spawn(async move {
+        // stream_forward takes a CancellationToken rather than this service's broadcast-based
+        // term_tx; bridge the two so a server shutdown still tears down the watch loop.
+        let ctx = CancellationToken::new();
+        let mut term_rx = self.term_tx.subscribe();
+        let ctx_for_shutdown = ctx.clone();
+        task_tracker.spawn(async move {
+            let _ = term_rx.recv().await;
+            ctx_for_shutdown.cancel();
+        });
+
+        let handle = self.task_tracker.spawn(async move {
             stream_forward::stream_forward(
+                ctx,
                 &file_path,
                 request.start_time.parse::<DateTime<Utc>>().ok(),
                 request.stop_time.parse::<DateTime<Utc>>().ok(),
@@ -107,13 +184,105 @@ impl LogRecordsService for LogRecordsImpl {
                 } else {
                     Some(&request.grep)
                 },
+                grep_spec.as_ref(),
+                // Not yet exposed on the request; stream filtering is rgkl-internal for now.
+                None,
                 request.follow_from(),
-                term_tx,
+                // Not yet exposed on the request; callers get no truncation.
+                0,
+                // Not yet exposed on the request; callers get the default debounce window.
+                stream_forward::DEFAULT_DEBOUNCE_INTERVAL,
+                forced_format,
+                request.compression(),
+                // Not yet exposed on the request; malformed lines are dropped with a tracing
+                // warning rather than surfaced to the client or aborting the stream.
+                MalformedLinePolicy::Skip,
                 tx,
             )
             .await;
         });
 
+        // A pathological grep pattern or a malformed-UTF8 boundary in the per-line matcher can
+        // unwind the spawned task; without this, the client just sees the output channel close
+        // with no status at all. Mirror that back as an Internal error instead of staying silent.
+        task_tracker.spawn(async move {
+            if let Err(join_err) = handle.await {
+                if let Ok(panic) = join_err.try_into_panic() {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| (*s).to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_owned());
+
+                    let _ = tx_for_panic
+                        .send(Err(Status::new(
+                            tonic::Code::Internal,
+                            format!("stream_forward panicked: {message}"),
+                        )))
+                        .await;
+                }
+            }
+        });
+
         Ok(Response::new(ReceiverStream::new(rx)))
     }
+
+    #[tracing::instrument]
+    async fn aggregate_backward(
+        &self,
+        request: Request<LogRecordsStreamRequest>,
+    ) -> Result<Response<LogRecordsAggregateResponse>, Status> {
+        let request = request.into_inner();
+        check_required_capabilities(&request.required_capabilities)?;
+        let file_path = self.get_log_filename(&request).map_err(|status| *status)?;
+
+        if request.bucket_duration_seconds <= 0 {
+            return Err(Status::new(
+                tonic::Code::InvalidArgument,
+                "bucket_duration_seconds must be positive",
+            ));
+        }
+        let bucket_duration = std::time::Duration::from_secs(request.bucket_duration_seconds as u64);
+
+        let forced_format: Option<FileFormat> = request.forced_format().into();
+        let grep_spec = grep_spec_from_request(&request);
+
+        let buckets = stream_backward::aggregate_backward(
+            &file_path,
+            request.start_time.parse::<DateTime<Utc>>().ok(),
+            request.stop_time.parse::<DateTime<Utc>>().ok(),
+            if request.grep.is_empty() {
+                None
+            } else {
+                Some(&request.grep)
+            },
+            grep_spec.as_ref(),
+            request.min_severity(),
+            forced_format,
+            bucket_duration,
+        )
+        .await
+        .map_err(Status::from)?;
+
+        Ok(Response::new(LogRecordsAggregateResponse {
+            buckets: buckets
+                .into_iter()
+                .map(|(bucket_start, count)| LogRecordBucket {
+                    bucket_start,
+                    count,
+                })
+                .collect(),
+        }))
+    }
+
+    #[tracing::instrument]
+    async fn get_capabilities(
+        &self,
+        _request: Request<CapabilitiesRequest>,
+    ) -> Result<Response<Capabilities>, Status> {
+        Ok(Response::new(Capabilities {
+            agent_version: env!("CARGO_PKG_VERSION").to_owned(),
+            features: SUPPORTED_FEATURES.iter().map(|&f| f.to_owned()).collect(),
+        }))
+    }
 }
@@ -1,34 +1,65 @@
 use std::error::Error;
-use std::fs::read_to_string;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
 
+use arc_swap::ArcSwap;
 use clap::{ArgAction, arg, command, value_parser};
+use prometheus_client::registry::Registry as MetricsRegistry;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
 use tokio::signal::ctrl_c;
 use tokio::signal::unix::{SignalKind, signal};
 use tokio::sync::broadcast::{self, Sender};
+use tokio::sync::watch;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+use tokio_stream::{
+    Stream, StreamExt,
+    wrappers::{TcpListenerStream, UnixListenerStream},
+};
 use tokio_util::task::TaskTracker;
-use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
-use tracing::info;
+use tonic::transport::Server;
+use tonic::transport::server::Connected;
+use tracing::{info, warn};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry as TracingRegistry, reload};
 use types::cluster_agent::FILE_DESCRIPTOR_SET;
 use types::cluster_agent::log_metadata_service_server::LogMetadataServiceServer;
 use types::cluster_agent::log_records_service_server::LogRecordsServiceServer;
 
 mod authorizer;
 mod config;
+#[cfg(feature = "http3")]
+mod http3;
 mod log_metadata;
 mod log_records;
+mod metrics;
 mod stream_util;
 use log_metadata::LogMetadataImpl;
+use log_metadata::compile_filename_patterns;
+use log_metadata::log_metadata_watcher::WatcherKind;
+use log_metadata::retention::RetentionConfig;
 use log_records::LogRecordsImpl;
+use metrics::LogMetadataMetrics;
 
-use crate::config::{Config, LoggingConfig, TlsConfig};
+use crate::config::{Config, Endpoint, LoggingConfig, WatcherConfig};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let config = parse_config().await?;
+    let (config_path, overrides) = parse_cli_args()?;
+    let (config, config_changed, _config_watcher_handle) =
+        Config::watch(config_path, overrides).await?;
+    let initial = config.load_full();
 
-    configure_logging(&config.logging)?;
+    let log_reload = configure_logging(&initial.logging)?;
 
     let (_, agent_health_service) = tonic_health::server::health_reporter();
     let reflection_service = tonic_reflection::server::Builder::configure()
@@ -37,24 +68,75 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let (term_tx, _term_rx) = broadcast::channel(1);
     let task_tracker = TaskTracker::new();
 
-    let mut server = enable_tls(Server::builder(), &config.tls)?;
+    let mut metrics_registry = MetricsRegistry::default();
+    let log_metadata_metrics = Arc::new(LogMetadataMetrics::new(&mut metrics_registry));
+    let filename_patterns = Arc::new(compile_filename_patterns(
+        &initial.watcher.log_filename_patterns,
+    )?);
 
-    info!("Starting cluster-agent on {}", config.address);
+    if initial.metrics.enabled {
+        let bind_addr = initial.metrics.addr.parse()?;
+        metrics::spawn(bind_addr, Arc::new(metrics_registry), term_tx.subscribe()).await?;
+    }
+
+    let mut server = Server::builder();
+
+    info!(
+        "Starting cluster-agent on {}",
+        initial
+            .listen
+            .iter()
+            .map(Endpoint::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let incoming = bind_listeners(&initial.listen, initial.unix_socket_mode).await?;
+    let (incoming, tls_reload) = wrap_with_tls(incoming, &initial.tls)?;
+    let logs_dir = Arc::new(ArcSwap::from_pointee(initial.logs_dir.clone()));
+
+    #[cfg(feature = "http3")]
+    if initial.transport == "h3" {
+        match initial.listen.iter().find_map(|endpoint| match endpoint {
+            Endpoint::Tcp(address) => Some(*address),
+            Endpoint::Unix(_) => None,
+        }) {
+            Some(bind_addr) => http3::spawn(bind_addr, &initial.tls, term_tx.subscribe()).await?,
+            None => warn!("transport = \"h3\" has no TCP endpoint in `listen` to bind"),
+        }
+    }
+
+    let _reload_task = spawn_config_reload_task(
+        config,
+        config_changed,
+        log_reload,
+        tls_reload,
+        logs_dir.clone(),
+    );
 
     server
         .add_service(agent_health_service)
         .add_service(reflection_service)
         .add_service(LogMetadataServiceServer::new(LogMetadataImpl::new(
-            config.logs_dir.clone(),
+            initial.logs_dir.clone(),
             term_tx.clone(),
             task_tracker.clone(),
+            watcher_kind(&initial.watcher),
+            Duration::from_millis(initial.watcher.debounce_interval_ms),
+            initial.watcher.recursive,
+            RetentionConfig {
+                max_size: initial.retention.max_size_bytes,
+                max_files: initial.retention.max_files,
+            },
+            Duration::from_millis(initial.shutdown.drain_timeout_ms),
+            log_metadata_metrics,
+            filename_patterns,
         )))
         .add_service(LogRecordsServiceServer::new(LogRecordsImpl::new(
-            config.logs_dir.clone(),
+            logs_dir,
             term_tx.clone(),
             task_tracker.clone(),
         )))
-        .serve_with_shutdown(config.address, shutdown(term_tx))
+        .serve_with_incoming_shutdown(incoming, shutdown(term_tx))
         .await
         .unwrap();
 
@@ -66,8 +148,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-#[allow(clippy::cognitive_complexity)]
-async fn parse_config() -> Result<Config, Box<(dyn Error + 'static)>> {
+/// Parses the CLI flags down to the raw config path/overrides [`Config::watch`] needs to keep
+/// re-parsing on every reload; unlike the one-shot [`Config::parse`] this used to call directly,
+/// the actual `Config` isn't produced here, since a `-p`/`--param` override has to survive a
+/// SIGHUP-triggered reload too.
+fn parse_cli_args() -> Result<(PathBuf, Vec<(String, String)>), Box<dyn Error>> {
     let matches = command!()
         .arg(
             arg!(
@@ -84,7 +169,7 @@ async fn parse_config() -> Result<Config, Box<(dyn Error + 'static)>> {
         .arg(arg!(-a --addr <ADDRESS> "Address to listen for connections"))
         .get_matches();
 
-    let config_path = matches.get_one::<PathBuf>("config").unwrap();
+    let config_path = matches.get_one::<PathBuf>("config").unwrap().clone();
     let mut overrides: Vec<(String, String)> = matches
         .get_many("param")
         .map_or_else(Vec::new, |params| params.cloned().collect());
@@ -93,9 +178,7 @@ async fn parse_config() -> Result<Config, Box<(dyn Error + 'static)>> {
         overrides.push(("addr".to_owned(), address.to_owned()));
     }
 
-    let config = Config::parse(config_path, overrides).await?;
-
-    Ok(config)
+    Ok((config_path, overrides))
 }
 
 fn parse_overrides(param: &str) -> Result<(String, String), String> {
@@ -109,45 +192,364 @@ fn parse_overrides(param: &str) -> Result<(String, String), String> {
     }
 }
 
-fn enable_tls(server: Server, tls_config: &TlsConfig) -> Result<Server, Box<dyn Error>> {
-    if !tls_config.enabled {
-        return Ok(server);
+/// Wraps `incoming` in a TLS handshake when `tls_config` is enabled, using a hot-reloadable
+/// acceptor (see [`config::TlsConfig::watch`]) rather than tonic's own `Server::tls_config`, since
+/// the latter is fixed once the server is built and can't pick up a cert-manager-rotated cert
+/// without a restart. Returns the (possibly wrapped) stream alongside a [`TlsReloadState`] handle
+/// that [`spawn_config_reload_task`] uses to swap in a whole new acceptor -- e.g. after
+/// `tls.client-auth` or a cert/key/CA path changes in the config -- on top of the per-file content
+/// reload `config::TlsConfig::watch` already does on its own. The handle must be kept alive for as
+/// long as the server runs; dropping it stops the reload task.
+///
+/// A connection that fails its TLS handshake is logged and dropped rather than propagated as a
+/// stream error, since one bad/stale client shouldn't tear down the whole listener.
+fn wrap_with_tls(
+    incoming: Pin<Box<dyn Stream<Item = io::Result<Connection>> + Send>>,
+    tls_config: &config::TlsConfig,
+) -> Result<
+    (
+        Pin<Box<dyn Stream<Item = io::Result<MaybeTlsConnection>> + Send>>,
+        Option<TlsReloadState>,
+    ),
+    Box<dyn Error>,
+> {
+    let Some((acceptor, watcher_handle)) = tls_config.watch()? else {
+        let incoming = incoming.map(|result| result.map(MaybeTlsConnection::Plain));
+        return Ok((Box::pin(incoming), None));
+    };
+
+    let slot = Arc::new(ArcSwap::from_pointee(acceptor));
+    let reload_state = TlsReloadState {
+        slot: slot.clone(),
+        watcher: Mutex::new(Some(watcher_handle)),
+    };
+
+    let incoming = incoming.then(move |result| {
+        let slot = slot.clone();
+        async move {
+            let conn = match result {
+                Ok(conn) => conn,
+                Err(error) => return Some(Err(error)),
+            };
+
+            match slot.load_full().accept(conn).await {
+                Ok(stream) => Some(Ok(MaybeTlsConnection::Tls(stream))),
+                Err(error) => {
+                    warn!("Rejecting connection: TLS handshake failed: {error}");
+                    None
+                }
+            }
+        }
+    });
+
+    Ok((
+        Box::pin(incoming.filter_map(|item| item)),
+        Some(reload_state),
+    ))
+}
+
+/// Lets [`spawn_config_reload_task`] swap in a freshly built TLS acceptor -- after a config reload
+/// changes `tls.client-auth` or a cert/key/CA path -- without rebuilding the listener or dropping
+/// connections already accepted. `watcher` holds the filesystem cert-rotation watcher
+/// ([`config::TlsConfig::watch`]) for whichever acceptor is currently live; reloading stops the
+/// old one so it doesn't keep watching stale paths.
+struct TlsReloadState {
+    slot: Arc<ArcSwap<TlsAcceptor>>,
+    watcher: Mutex<Option<config::TlsCertWatcherHandle>>,
+}
+
+impl TlsReloadState {
+    fn reload(&self, tls_config: &config::TlsConfig) -> Result<(), Box<dyn Error>> {
+        let Some((acceptor, watcher_handle)) = tls_config.watch()? else {
+            return Err("tls.enabled can't be toggled on/off without a restart".into());
+        };
+
+        self.slot.store(Arc::new(acceptor));
+        if let Some(previous) = self.watcher.lock().unwrap().replace(watcher_handle) {
+            previous.stop();
+        }
+
+        Ok(())
     }
+}
 
-    let cert = read_to_string(tls_config.cert_file.as_ref().unwrap())?;
-    let key = read_to_string(tls_config.key_file.as_ref().unwrap())?;
-    let server_identity = Identity::from_pem(cert, key);
+/// A connection accepted from either a TCP or a Unix domain socket listener, unified so both can
+/// be merged into the single incoming stream tonic's `serve_with_incoming_shutdown` expects.
+enum Connection {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
 
-    let mut server_tls_config = ServerTlsConfig::new().identity(server_identity);
+impl AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
 
-    #[allow(clippy::collapsible_if)]
-    if let Some(client_auth) = &tls_config.client_auth {
-        if client_auth == "require-and-verify" {
-            let client_ca_cert = read_to_string(tls_config.ca_file.as_ref().unwrap())?;
-            let client_ca_cert = Certificate::from_pem(client_ca_cert);
+impl AsyncWrite for Connection {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
 
-            server_tls_config = server_tls_config.client_ca_root(client_ca_cert);
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Unix(stream) => Pin::new(stream).poll_flush(cx),
         }
     }
 
-    server.tls_config(server_tls_config).map_err(Into::into)
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
 }
 
-fn configure_logging(logging_config: &LoggingConfig) -> Result<(), Box<dyn Error>> {
-    if !logging_config.enabled {
-        return Ok(());
+impl Connected for Connection {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
+
+/// A [`Connection`], optionally wrapped in TLS by [`wrap_with_tls`]. Unified the same way
+/// `Connection` unifies TCP and Unix so tonic sees a single IO type regardless of whether TLS is
+/// enabled in `config.tls`.
+enum MaybeTlsConnection {
+    Plain(Connection),
+    Tls(TlsStream<Connection>),
+}
+
+impl AsyncRead for MaybeTlsConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(conn) => Pin::new(conn).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for MaybeTlsConnection {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(conn) => Pin::new(conn).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
     }
 
-    let sub_builder =
-        tracing_subscriber::fmt().with_max_level(tracing::Level::from_str(&logging_config.level)?);
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(conn) => Pin::new(conn).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
 
-    if logging_config.format == "pretty" {
-        sub_builder.pretty().init();
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(conn) => Pin::new(conn).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+impl Connected for MaybeTlsConnection {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
+
+/// Binds a listener per entry in `endpoints` (TCP or `unix://`) and merges them into a single
+/// incoming connection stream, so a dual-stack config can listen on both `0.0.0.0` and `[::]`, a
+/// Unix domain socket, or any other combination, as one tonic server.
+///
+/// A stale file left behind at a Unix socket path (e.g. from an unclean shutdown) is removed
+/// before binding, since `UnixListener::bind` otherwise fails with `AddrInUse`. When
+/// `unix_socket_mode` is set, it's applied to the socket file right after bind so node-local
+/// peers without the agent's own uid/gid can still connect.
+async fn bind_listeners(
+    endpoints: &[Endpoint],
+    unix_socket_mode: Option<u32>,
+) -> Result<Pin<Box<dyn Stream<Item = io::Result<Connection>> + Send>>, Box<dyn Error>> {
+    let mut incoming: Option<Pin<Box<dyn Stream<Item = io::Result<Connection>> + Send>>> = None;
+
+    for endpoint in endpoints {
+        let stream: Pin<Box<dyn Stream<Item = io::Result<Connection>> + Send>> = match endpoint {
+            Endpoint::Tcp(address) => {
+                let listener = TcpListener::bind(address).await?;
+                Box::pin(TcpListenerStream::new(listener).map(|result| result.map(Connection::Tcp)))
+            }
+            Endpoint::Unix(path) => {
+                if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+
+                let listener = UnixListener::bind(path)?;
+
+                if let Some(mode) = unix_socket_mode {
+                    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+                }
+
+                Box::pin(UnixListenerStream::new(listener).map(|result| result.map(Connection::Unix)))
+            }
+        };
+
+        incoming = Some(match incoming {
+            None => stream,
+            Some(existing) => Box::pin(existing.merge(stream)),
+        });
+    }
+
+    incoming.ok_or_else(|| "no addresses configured to bind".into())
+}
+
+fn watcher_kind(watcher_config: &WatcherConfig) -> WatcherKind {
+    if watcher_config.backend == "poll" {
+        WatcherKind::Poll(Duration::from_millis(watcher_config.poll_interval_ms))
     } else {
-        sub_builder.json().init();
+        WatcherKind::Native
     }
+}
 
-    Ok(())
+/// The formatting + level-filter layer [`configure_logging`] installs at startup and
+/// [`apply_reloadable_config`] swaps in on a reload. Boxed so the `reload::Layer` doesn't need to
+/// know ahead of time whether the configured format is `pretty` or JSON.
+type BoxedLogLayer = Box<dyn Layer<TracingRegistry> + Send + Sync>;
+type LogReloadHandle = reload::Handle<BoxedLogLayer, TracingRegistry>;
+
+fn build_log_layer(logging_config: &LoggingConfig) -> Result<BoxedLogLayer, Box<dyn Error>> {
+    let filter = LevelFilter::from(tracing::Level::from_str(&logging_config.level)?);
+
+    Ok(if logging_config.format == "pretty" {
+        tracing_subscriber::fmt::layer().pretty().with_filter(filter).boxed()
+    } else {
+        tracing_subscriber::fmt::layer().json().with_filter(filter).boxed()
+    })
+}
+
+/// Installs the global tracing subscriber, or does nothing when `logging.enabled` is `false`.
+/// Returns a handle [`apply_reloadable_config`] can later use to swap in a new level/format on a
+/// config reload; once no subscriber has been installed at startup, there's nothing a reload can
+/// retroactively hook into, so `logging.enabled` itself can't be turned on at runtime.
+fn configure_logging(logging_config: &LoggingConfig) -> Result<Option<LogReloadHandle>, Box<dyn Error>> {
+    if !logging_config.enabled {
+        return Ok(None);
+    }
+
+    let (layer, reload_handle) = reload::Layer::new(build_log_layer(logging_config)?);
+    tracing_subscriber::registry().with(layer).init();
+
+    Ok(Some(reload_handle))
+}
+
+/// Spawned once at startup, this task is the consuming half of the reload path whose
+/// filesystem/SIGHUP-triggered producing half lives in [`config::Config::watch`]: each time
+/// `config_changed` fires, the newly swapped-in [`Config`] is diffed against the last one this
+/// task saw and [`apply_reloadable_config`] re-applies whatever changed. Runs for the life of the
+/// process; `config_changed` only closes if the watch task itself panics, so there's nothing
+/// meaningful to do beyond letting this task end too.
+fn spawn_config_reload_task(
+    config: Arc<ArcSwap<Config>>,
+    mut config_changed: watch::Receiver<()>,
+    log_reload: Option<LogReloadHandle>,
+    tls_reload: Option<TlsReloadState>,
+    logs_dir: Arc<ArcSwap<PathBuf>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut applied = config.load_full();
+
+        while config_changed.changed().await.is_ok() {
+            let reloaded = config.load_full();
+            apply_reloadable_config(
+                &applied,
+                &reloaded,
+                log_reload.as_ref(),
+                tls_reload.as_ref(),
+                &logs_dir,
+            );
+            applied = reloaded;
+        }
+    })
+}
+
+/// Re-applies whatever changed between `previous` and `reloaded` that can safely take effect
+/// without restarting the agent: `container-logs-dir`, logging level/format, and, when TLS was
+/// enabled at startup, `tls.client-auth` and the cert/key/CA file paths. `listen`/
+/// `unix-socket-mode`/`transport` are baked into the listeners bound back in `main`, so a
+/// difference there is only logged -- the operator needs to restart the agent to pick it up.
+/// `cluster-agent`'s config has no grep-related defaults to begin with (grep patterns are
+/// entirely request-scoped; see `log_records::grep_spec_from_request`), so there's nothing to
+/// reload on that front. The `Authorizer`'s k8s client config is re-inferred from the ambient
+/// kubeconfig/in-cluster environment on every call already (see `authorizer::Authorizer::new`),
+/// so it has no baked-in state here to refresh either.
+fn apply_reloadable_config(
+    previous: &Config,
+    reloaded: &Config,
+    log_reload: Option<&LogReloadHandle>,
+    tls_reload: Option<&TlsReloadState>,
+    logs_dir: &Arc<ArcSwap<PathBuf>>,
+) {
+    if reloaded.listen != previous.listen {
+        warn!("listen address changed in reloaded configuration; restart the agent to apply it");
+    }
+
+    if reloaded.logs_dir != previous.logs_dir {
+        logs_dir.store(Arc::new(reloaded.logs_dir.clone()));
+        info!(
+            "Reloaded container-logs-dir configuration: {}",
+            reloaded.logs_dir.to_string_lossy()
+        );
+    }
+
+    if reloaded.logging.level != previous.logging.level
+        || reloaded.logging.format != previous.logging.format
+    {
+        match (log_reload, build_log_layer(&reloaded.logging)) {
+            (Some(handle), Ok(layer)) => match handle.reload(layer) {
+                Ok(()) => info!("Reloaded logging configuration"),
+                Err(error) => warn!("Failed to apply reloaded logging configuration: {error}"),
+            },
+            (Some(_), Err(error)) => {
+                warn!("Discarding invalid logging level in reloaded configuration: {error}");
+            }
+            (None, _) => warn!(
+                "logging.enabled was false at startup; it can't be turned on without a restart"
+            ),
+        }
+    }
+
+    let tls_changed = reloaded.tls.client_auth != previous.tls.client_auth
+        || reloaded.tls.cert_file != previous.tls.cert_file
+        || reloaded.tls.key_file != previous.tls.key_file
+        || reloaded.tls.ca_file != previous.tls.ca_file;
+
+    if tls_changed {
+        match tls_reload {
+            Some(state) => match state.reload(&reloaded.tls) {
+                Ok(()) => info!("Reloaded TLS configuration"),
+                Err(error) => warn!("Failed to apply reloaded TLS configuration: {error}"),
+            },
+            None => {
+                warn!("tls.enabled was false at startup; it can't be turned on without a restart");
+            }
+        }
+    }
 }
 
 async fn shutdown(term_tx: Sender<()>) {
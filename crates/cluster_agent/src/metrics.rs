@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::Registry;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::Receiver as BcReceiver;
+use tracing::{info, warn};
+
+/// Labels a per-namespace counter by the namespace it was observed in and the node the agent is
+/// running on, so a query can be scoped to either axis.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct NamespaceLabels {
+    pub namespace: String,
+    pub node: String,
+}
+
+/// Labels a watch-event counter the same way as [`NamespaceLabels`], plus the event's `type`
+/// (`"ADDED"`/`"MODIFIED"`/`"DELETED"`/`"ROTATED"`), so churn can be broken down by kind.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct WatchEventLabels {
+    pub namespace: String,
+    pub node: String,
+    pub kind: String,
+}
+
+/// Operational counters and gauges for `LogMetadataImpl`, registered into a shared [`Registry`]
+/// and scraped over the `/metrics` HTTP endpoint served by [`serve`].
+#[derive(Debug, Clone)]
+pub struct LogMetadataMetrics {
+    /// Log files discovered per namespace, incremented once per file on every `list` call.
+    pub files_discovered: Family<NamespaceLabels, Counter>,
+    /// Total bytes reported across every file discovered, per namespace.
+    pub bytes_observed: Family<NamespaceLabels, Counter>,
+    /// Watch sessions currently open, i.e. active `Watch` streams this agent is serving.
+    pub watch_sessions_open: Gauge,
+    /// Watch events emitted, per namespace/node/kind.
+    pub watch_events_total: Family<WatchEventLabels, Counter>,
+}
+
+impl LogMetadataMetrics {
+    /// Builds the metric instruments and registers each under the `log_metadata` namespace.
+    pub fn new(registry: &mut Registry) -> Self {
+        let files_discovered = Family::<NamespaceLabels, Counter>::default();
+        let bytes_observed = Family::<NamespaceLabels, Counter>::default();
+        let watch_sessions_open = Gauge::default();
+        let watch_events_total = Family::<WatchEventLabels, Counter>::default();
+
+        registry.register(
+            "log_metadata_files_discovered",
+            "Log files discovered during list, per namespace",
+            files_discovered.clone(),
+        );
+        registry.register(
+            "log_metadata_bytes_observed",
+            "Total bytes reported across discovered log files, per namespace",
+            bytes_observed.clone(),
+        );
+        registry.register(
+            "log_metadata_watch_sessions_open",
+            "Watch streams currently open",
+            watch_sessions_open.clone(),
+        );
+        registry.register(
+            "log_metadata_watch_events_total",
+            "Watch events emitted, per namespace and event kind",
+            watch_events_total.clone(),
+        );
+
+        Self {
+            files_discovered,
+            bytes_observed,
+            watch_sessions_open,
+            watch_events_total,
+        }
+    }
+}
+
+/// Accepts connections on `bind_addr` until `term_rx` fires, serving `registry` in Prometheus text
+/// format on every request path (there's only one thing to scrape, so the path is ignored). A
+/// minimal hand-rolled HTTP/1.1 responder rather than pulling in an HTTP server crate, matching how
+/// [`crate::main`] builds its own listener plumbing instead of depending on one.
+async fn serve(listener: TcpListener, registry: Arc<Registry>, mut term_rx: BcReceiver<()>) {
+    loop {
+        tokio::select! {
+            biased;
+            _ = term_rx.recv() => break,
+            accepted = listener.accept() => {
+                let Ok((stream, _peer_addr)) = accepted else { continue };
+                let registry = registry.clone();
+
+                tokio::spawn(async move {
+                    if let Err(error) = handle_connection(stream, &registry).await {
+                        warn!("Metrics connection handling failed: {error}");
+                    }
+                });
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    mut stream: tokio::net::TcpStream,
+    registry: &Registry,
+) -> Result<(), std::io::Error> {
+    // Only the request line is needed to know a request arrived at all; headers and any body are
+    // read and discarded implicitly by responding without consuming them further.
+    let mut buf = [0u8; 1024];
+    stream.read(&mut buf).await?;
+
+    let mut body = String::new();
+    encode(&mut body, registry)
+        .map_err(|error| std::io::Error::other(format!("failed to encode metrics: {error}")))?;
+
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/openmetrics-text; version=1.0.0; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+/// Binds `bind_addr` and spawns the `/metrics` HTTP server in the background, returning
+/// immediately. Intended to be called once at startup and kept running for the agent's lifetime,
+/// the same way [`crate::http3::spawn`] starts its own listener.
+pub async fn spawn(
+    bind_addr: SocketAddr,
+    registry: Arc<Registry>,
+    term_rx: BcReceiver<()>,
+) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(bind_addr).await?;
+
+    info!("Starting metrics listener on {bind_addr}");
+    tokio::spawn(serve(listener, registry, term_rx));
+
+    Ok(())
+}
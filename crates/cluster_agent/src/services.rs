@@ -5,13 +5,16 @@ use tonic::{Request, Response, Status};
 use types::cluster_agent::log_metadata_service_server::LogMetadataService;
 use types::cluster_agent::log_records_service_server::LogRecordsService;
 use types::cluster_agent::{
-    LogMetadataList, LogMetadataListRequest, LogMetadataWatchEvent, LogMetadataWatchRequest,
-    LogRecord, LogRecordsStreamRequest,
+    LogMetadataList, LogMetadataListRequest, LogMetadataListStreamRequest,
+    LogMetadataListStreamResponse, LogMetadataWatchEvent, LogMetadataWatchRequest, LogRecord,
+    LogRecordsStreamRequest,
 };
 
 type AgentResult<T> = Result<Response<T>, Status>;
 type WatchResponseStream =
     Pin<Box<dyn Stream<Item = Result<LogMetadataWatchEvent, Status>> + Send>>;
+type ListStreamResponseStream =
+    Pin<Box<dyn Stream<Item = Result<LogMetadataListStreamResponse, Status>> + Send>>;
 type BackwardForwardResponseStream = Pin<Box<dyn Stream<Item = Result<LogRecord, Status>> + Send>>;
 
 #[derive(Debug, Clone)]
@@ -20,6 +23,7 @@ pub struct ClusterAgent;
 #[tonic::async_trait]
 impl LogMetadataService for ClusterAgent {
     type WatchStream = WatchResponseStream;
+    type ListStreamStream = ListStreamResponseStream;
     async fn list(
         &self,
         _request: Request<LogMetadataListRequest>,
@@ -32,6 +36,12 @@ impl LogMetadataService for ClusterAgent {
     ) -> AgentResult<Self::WatchStream> {
         todo!()
     }
+    async fn list_stream(
+        &self,
+        _request: Request<LogMetadataListStreamRequest>,
+    ) -> AgentResult<Self::ListStreamStream> {
+        todo!()
+    }
 }
 
 #[tonic::async_trait]
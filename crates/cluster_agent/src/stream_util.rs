@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use tokio::select;
 use tokio::sync::{broadcast::Sender as BcSender, mpsc};
 use tokio_stream::wrappers::ReceiverStream;
@@ -5,15 +7,17 @@ use tonic::Status;
 
 /// Wraps a `mpsc::Receiver<Result<T, Status>>` with a termination signal.
 ///
-/// When the broadcast channel receives a shutdown signal, this wrapper emits a
-/// single `Err(Status::unavailable("server shutting down"))` and then
-/// terminates the stream. If the inner receiver yields an `Err(Status)`, that
-/// error is forwarded and the stream terminates without emitting an additional
-/// shutdown error. When the inner receiver ends (returns `None`), the stream
-/// completes normally.
+/// Before shutdown, items (and a terminal `Err(Status)`) from the inner receiver are forwarded
+/// as soon as they arrive, and the stream completes normally if `rx` closes on its own. Once the
+/// broadcast channel signals shutdown, the wrapper stops waiting indefinitely on new input but
+/// keeps forwarding whatever is already queued in `rx`, for up to `drain_timeout`, so an in-flight
+/// `StreamForward`/`StreamBackward` response isn't truncated by the shutdown signal itself. Once
+/// `rx` closes or `drain_timeout` elapses, the wrapper emits a single
+/// `Err(Status::unavailable("server shutting down"))` and terminates.
 pub fn wrap_with_shutdown<T: Send + 'static>(
     mut rx: mpsc::Receiver<Result<T, Status>>,
     term_tx: BcSender<()>,
+    drain_timeout: Duration,
 ) -> ReceiverStream<Result<T, Status>> {
     let (out_tx, out_rx) = mpsc::channel(100);
     let mut term_rx = term_tx.subscribe();
@@ -22,32 +26,57 @@ pub fn wrap_with_shutdown<T: Send + 'static>(
         loop {
             select! {
                 biased;
-                // Prefer shutdown over completing the stream to avoid EOF on shutdown.
+                // Prefer an already-queued item/error over noticing shutdown, so nothing that
+                // was already in flight at the moment of the signal gets dropped.
+                maybe = rx.recv() => {
+                    match maybe {
+                        Some(Ok(item)) => {
+                            if out_tx.send(Ok(item)).await.is_err() {
+                                return;
+                            }
+                        }
+                        Some(Err(status)) => {
+                            let _ = out_tx.send(Err(status)).await;
+                            return;
+                        }
+                        None => return,
+                    }
+                }
                 _ = term_rx.recv() => {
-                    let _ = out_tx
-                        .send(Err(Status::new(tonic::Code::Unavailable, "server shutting down")))
-                        .await;
                     break;
                 }
-                // Propagate inner items
+            }
+        }
+
+        // Shutdown signaled: keep draining `rx` instead of truncating it, bounded by
+        // `drain_timeout` so a producer that never closes on its own (e.g. a `StreamForward`
+        // still following the file) can't hang shutdown indefinitely.
+        let deadline = tokio::time::sleep(drain_timeout);
+        tokio::pin!(deadline);
+
+        loop {
+            select! {
                 maybe = rx.recv() => {
                     match maybe {
                         Some(Ok(item)) => {
                             if out_tx.send(Ok(item)).await.is_err() {
-                                break;
+                                return;
                             }
                         }
                         Some(Err(status)) => {
                             let _ = out_tx.send(Err(status)).await;
-                            break;
-                        }
-                        None => {
-                            break;
+                            return;
                         }
+                        None => break,
                     }
                 }
+                () = &mut deadline => break,
             }
         }
+
+        let _ = out_tx
+            .send(Err(Status::new(tonic::Code::Unavailable, "server shutting down")))
+            .await;
         // Drop out_tx to close the outer stream
     });
 
@@ -57,10 +86,14 @@ pub fn wrap_with_shutdown<T: Send + 'static>(
 #[cfg(test)]
 mod tests {
     use super::wrap_with_shutdown;
+    use std::time::Duration;
     use tokio::sync::{broadcast, mpsc};
     use tokio_stream::StreamExt;
     use tonic::Status;
 
+    // Short enough that tests exercising the deadline stay fast.
+    const DRAIN_TIMEOUT: Duration = Duration::from_millis(50);
+
     // Helper to extract a Status from a Result value in tests
     fn status_code<T>(r: Result<T, Status>) -> tonic::Code {
         r.err().unwrap().code()
@@ -72,7 +105,7 @@ mod tests {
         let (term_tx, _term_rx) = broadcast::channel::<()>(1);
 
         // Wrap (keep a clone of the sender alive)
-        let mut out = wrap_with_shutdown(rx, term_tx.clone());
+        let mut out = wrap_with_shutdown(rx, term_tx.clone(), DRAIN_TIMEOUT);
 
         // Send some items then close
         tx.send(Ok(1)).await.unwrap();
@@ -90,7 +123,7 @@ mod tests {
     async fn forwards_inner_error_and_terminates() {
         let (tx, rx) = mpsc::channel::<Result<i32, Status>>(8);
         let (term_tx, _term_rx) = broadcast::channel::<()>(1);
-        let mut out = wrap_with_shutdown(rx, term_tx.clone());
+        let mut out = wrap_with_shutdown(rx, term_tx.clone(), DRAIN_TIMEOUT);
 
         tx.send(Err(Status::aborted("boom"))).await.unwrap();
         // After an error, wrapper should terminate; additional items are ignored.
@@ -103,12 +136,13 @@ mod tests {
     async fn emits_unavailable_on_shutdown_without_items() {
         let (_tx, rx) = mpsc::channel::<Result<i32, Status>>(8);
         let (term_tx, _term_rx) = broadcast::channel::<()>(1);
-        let mut out = wrap_with_shutdown(rx, term_tx.clone());
+        let mut out = wrap_with_shutdown(rx, term_tx.clone(), DRAIN_TIMEOUT);
 
         // Signal shutdown
         let _ = term_tx.send(());
 
-        // First (and only) item should be UNAVAILABLE
+        // `_tx` stays alive and never sends anything further, so the drain deadline has to
+        // elapse before the UNAVAILABLE terminator shows up.
         let first = out.next().await.unwrap();
         assert_eq!(status_code(first), tonic::Code::Unavailable);
         assert!(out.next().await.is_none());
@@ -118,35 +152,56 @@ mod tests {
     async fn emits_unavailable_after_some_items_on_shutdown() {
         let (tx, rx) = mpsc::channel::<Result<i32, Status>>(8);
         let (term_tx, _term_rx) = broadcast::channel::<()>(1);
-        let mut out = wrap_with_shutdown(rx, term_tx.clone());
+        let mut out = wrap_with_shutdown(rx, term_tx.clone(), DRAIN_TIMEOUT);
 
         // Send one item
         tx.send(Ok(42)).await.unwrap();
         let first = out.next().await.unwrap();
         assert_eq!(first.as_ref().ok(), Some(&42));
 
-        // Now signal shutdown
+        // Now signal shutdown, without closing `tx` — the wrapper must still fall back to the
+        // UNAVAILABLE terminator once the drain deadline elapses.
+        let _ = term_tx.send(());
+
+        let second = out.next().await.unwrap();
+        assert_eq!(status_code(second), tonic::Code::Unavailable);
+        assert!(out.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn drains_queued_item_before_shutdown_terminator() {
+        let (tx, rx) = mpsc::channel::<Result<i32, Status>>(8);
+        let (term_tx, _term_rx) = broadcast::channel::<()>(1);
+        let mut out = wrap_with_shutdown(rx, term_tx.clone(), DRAIN_TIMEOUT);
+
+        // Queue an item and signal shutdown before anything is read, then close the sender:
+        // the already-queued item must still be forwarded ahead of the UNAVAILABLE terminator,
+        // rather than being truncated by the shutdown signal.
+        tx.send(Ok(7)).await.unwrap();
         let _ = term_tx.send(());
+        drop(tx);
+
+        let first = out.next().await.unwrap();
+        assert_eq!(first.as_ref().ok(), Some(&7));
 
-        // Next should be the UNAVAILABLE error and then end
         let second = out.next().await.unwrap();
         assert_eq!(status_code(second), tonic::Code::Unavailable);
         assert!(out.next().await.is_none());
     }
 
     #[tokio::test]
-    async fn prefers_shutdown_over_inner_error() {
+    async fn drains_queued_inner_error_instead_of_preferring_shutdown() {
         let (tx, rx) = mpsc::channel::<Result<i32, Status>>(8);
         let (term_tx, _term_rx) = broadcast::channel::<()>(1);
-        let mut out = wrap_with_shutdown(rx, term_tx.clone());
+        let mut out = wrap_with_shutdown(rx, term_tx.clone(), DRAIN_TIMEOUT);
 
-        // Push an inner error, then signal shutdown; wrapper should forward the
-        // shutdown status (biased select prefers shutdown if both are ready).
+        // An inner error queued ahead of the shutdown signal is still forwarded as-is: draining
+        // what's already queued takes priority over the shutdown terminator.
         tx.send(Err(Status::unknown("inner"))).await.unwrap();
         let _ = term_tx.send(());
 
         let first = out.next().await.unwrap();
-        assert_eq!(status_code(first), tonic::Code::Unavailable);
+        assert_eq!(status_code(first), tonic::Code::Unknown);
         assert!(out.next().await.is_none());
     }
 }
@@ -27,6 +27,9 @@ pub enum FsWatcherError {
 
     #[error("Log directory not found: {0}")]
     DirNotFound(String),
+
+    #[error("Invalid grep pattern: {0}")]
+    InvalidGrepPattern(#[from] regex::Error),
 }
 
 impl From<FsWatcherError> for Status {
@@ -37,6 +40,9 @@ impl From<FsWatcherError> for Status {
             FsWatcherError::DirNotFound(_) => {
                 Self::new(tonic::Code::NotFound, watcher_error.to_string())
             }
+            FsWatcherError::InvalidGrepPattern(_) => {
+                Self::new(tonic::Code::InvalidArgument, watcher_error.to_string())
+            }
         }
     }
 }
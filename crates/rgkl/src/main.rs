@@ -15,7 +15,7 @@
 use std::{io::stdout, process::ExitCode, thread};
 
 use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use signal_hook::{
     consts::{SIGINT, SIGTERM},
     iterator::Signals,
@@ -26,6 +26,28 @@ use rgkl::{stream_backward, stream_forward, z};
 
 mod error;
 
+/// Output mode for the streaming subcommands: `text` writes the raw container log line (the
+/// longstanding default), `json` wraps each record in a machine-readable envelope and serializes
+/// errors to stderr as JSON instead of `Error: {err}`, so callers like the Go control plane don't
+/// have to re-parse the text format.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Writes `err` to stderr in whichever shape `format` calls for.
+fn print_error(format: OutputFormat, err: impl std::fmt::Display) {
+    match format {
+        OutputFormat::Text => eprintln!("Error: {err:#}"),
+        OutputFormat::Json => {
+            let envelope = serde_json::json!({ "error": err.to_string() });
+            eprintln!("{envelope}");
+        }
+    }
+}
+
 // See https://github.com/BurntSushi/ripgrep/blob/master/crates/core/main.rs#L19
 #[cfg(all(target_env = "musl", target_pointer_width = "64"))]
 #[global_allocator]
@@ -36,6 +58,9 @@ static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 }
 
 #[derive(Subcommand, Debug)]
@@ -114,7 +139,7 @@ fn main() -> ExitCode {
             ) {
                 Ok(_) => ExitCode::SUCCESS,
                 Err(err) => {
-                    eprintln!("Error: {:#}", err);
+                    print_error(cli.format, err);
                     ExitCode::FAILURE
                 }
             }
@@ -129,7 +154,7 @@ fn main() -> ExitCode {
             match stream_backward::run(file, *start_time, *stop_time, grep, term_rx, &mut stdout) {
                 Ok(_) => ExitCode::SUCCESS,
                 Err(err) => {
-                    eprintln!("Error: {:#}", err);
+                    print_error(cli.format, err);
                     ExitCode::FAILURE
                 }
             }
@@ -0,0 +1,65 @@
+// Copyright 2024-2026 The Kubetail Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error as StdError;
+use std::path::PathBuf;
+
+use thiserror::Error;
+use tonic::Status;
+
+/// Why `stream_forward`'s listen loop stopped sending records, carried out of the loop so the
+/// final message on the channel tells the client something more useful than a single hardcoded
+/// status. Distinguishes a clean server drain from a watcher that actually failed.
+#[derive(Error, Debug)]
+pub enum ShutdownError {
+    /// The caller cancelled the stream's `CancellationToken` (server shutdown, client hangup),
+    /// not a failure in the watcher itself.
+    #[error("server is shutting down")]
+    GracefulShutdown,
+
+    /// The notify watcher reported an error, its callback channel closed, or reconciling the
+    /// file against disk after an event failed outright -- the stream can no longer be trusted
+    /// to see further changes.
+    #[error("file watcher failed: {source}")]
+    WatcherFailed {
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+
+    /// The watched file disappeared and didn't come back as an in-place rotation
+    /// (`reopen_on_rotation_or_truncation` already treats a momentary gap between the kubelet
+    /// removing and recreating it as transient; this fires only once reading the path fails
+    /// outright).
+    #[error("log file removed: {}", path.display())]
+    FileRemoved { path: PathBuf },
+
+    /// Reserved for a future watcher-configuration reload (e.g. a grep or namespace filter
+    /// changing mid-stream); nothing produces this yet.
+    #[error("stream configuration was reloaded")]
+    ConfigReloaded,
+}
+
+impl From<ShutdownError> for Status {
+    fn from(err: ShutdownError) -> Self {
+        match &err {
+            ShutdownError::GracefulShutdown | ShutdownError::ConfigReloaded => {
+                Self::new(tonic::Code::Unavailable, err.to_string())
+            }
+            ShutdownError::FileRemoved { .. } => Self::new(tonic::Code::NotFound, err.to_string()),
+            ShutdownError::WatcherFailed { .. } => {
+                Self::new(tonic::Code::Internal, err.to_string())
+            }
+        }
+    }
+}
@@ -12,11 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs::File, path::PathBuf};
+use std::{collections::BTreeMap, fs::File, path::PathBuf, time::Duration};
 
-use tokio::sync::{broadcast::Sender as BcSender, mpsc::Sender};
+use tokio::sync::{
+    broadcast::{self, Sender as BcSender},
+    mpsc::{self, Sender},
+};
+use tokio_util::sync::CancellationToken;
 use tonic::Status;
-use types::cluster_agent::LogRecord;
+use types::cluster_agent::{Compression, LogRecord, Severity};
 
 use chrono::{DateTime, Utc};
 use grep::{
@@ -27,51 +31,92 @@ use grep::{
 use crate::{
     fs_watcher_error::FsWatcherError,
     util::{
-        format::FileFormat,
-        matcher::{LogFileRegexMatcher, PassThroughMatcher},
+        format::{year_hint_from_mtime, FileFormat},
+        matcher::{
+            GrepSpec, LogFileRegexMatcher, PassThroughMatcher, RegexSetMatcher,
+            SeverityFilterMatcher,
+        },
         offset::{find_nearest_offset_since, find_nearest_offset_until},
-        reader::{ReverseLineReader, TermReader},
-        writer::{process_output, CallbackWriter},
+        reader::{CriLineReassemblingReverseReader, ReverseLineReader, TermReader},
+        writer::{spawn_line_processor, CallbackWriter, MalformedLinePolicy},
     },
 };
 
+#[allow(clippy::too_many_arguments)]
 pub async fn stream_backward(
     path: &PathBuf,
     start_time: Option<DateTime<Utc>>,
     stop_time: Option<DateTime<Utc>>,
     grep: Option<&str>,
+    grep_spec: Option<&GrepSpec>,
+    min_severity: Severity,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
     term_tx: BcSender<()>,
     sender: Sender<Result<LogRecord, Status>>,
 ) {
-    let result = stream_backward_internal(path, start_time, stop_time, grep, &term_tx, &sender);
+    // stream_backward_internal is synchronous (it drives a blocking searcher loop), so it can't
+    // await term_tx directly; bridge the broadcast-based termination signal into a
+    // CancellationToken it can poll between reads instead, same as log_records.rs does to adapt
+    // this service's shutdown signal for stream_forward's CancellationToken-based API.
+    let ctx = CancellationToken::new();
+    let mut term_rx = term_tx.subscribe();
+    let ctx_for_shutdown = ctx.clone();
+    tokio::spawn(async move {
+        let _ = term_rx.recv().await;
+        ctx_for_shutdown.cancel();
+    });
+
+    let result = stream_backward_internal(
+        path,
+        start_time,
+        stop_time,
+        grep,
+        grep_spec,
+        min_severity,
+        forced_format,
+        compression,
+        malformed_line_policy,
+        &ctx,
+        &sender,
+    );
 
     if let Err(error) = result {
         let _ = sender.send(Err(error.into())).await;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn stream_backward_internal(
     path: &PathBuf,
     start_time: Option<DateTime<Utc>>,
     stop_time: Option<DateTime<Utc>>,
     grep: Option<&str>,
-    term_tx: &BcSender<()>,
+    grep_spec: Option<&GrepSpec>,
+    min_severity: Severity,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
+    ctx: &CancellationToken,
     sender: &Sender<Result<LogRecord, Status>>,
 ) -> Result<(), FsWatcherError> {
     // Open file
     let file = File::open(path)?;
     let max_offset = file.metadata()?.len();
 
-    // Determine format based on filename
-    let format = if path.to_string_lossy().ends_with("-json.log") {
-        FileFormat::Docker
-    } else {
-        FileFormat::CRI
-    };
+    // Anchors year-less formats (klog, classic syslog) to this file's own last-modified year,
+    // since neither of those headers carries one.
+    let year_hint = year_hint_from_mtime(&file);
+
+    // Determine format: an explicit override wins, then the filename, then content-sniffing.
+    let format = FileFormat::resolve(path, &file, forced_format, year_hint)?;
 
     // Get start pos
     let start_pos: u64 = if let Some(ts) = start_time {
-        if let Some(offset) = find_nearest_offset_since(&file, ts, 0, max_offset, format)? {
+        if let Some(offset) =
+            find_nearest_offset_since(&file, ts, 0, max_offset, format, year_hint, true)?
+        {
             offset.byte_offset
         } else {
             return Ok(()); // No records, exit early
@@ -82,7 +127,9 @@ fn stream_backward_internal(
 
     // Get end pos
     let end_pos: u64 = if let Some(ts) = stop_time {
-        if let Some(offset) = find_nearest_offset_until(&file, ts, 0, max_offset, format)? {
+        if let Some(offset) =
+            find_nearest_offset_until(&file, ts, 0, max_offset, format, year_hint, true)?
+        {
             offset.byte_offset + offset.line_length
         } else {
             return Ok(()); // No records, exit early
@@ -91,11 +138,14 @@ fn stream_backward_internal(
         max_offset
     };
 
+    // Reassemble kubelet-split CRI P/F runs walking backward before the searcher ever sees a
+    // fragment, same as stream_forward does going forward -- see
+    // `CriLineReassemblingReverseReader`'s doc comment for how the two directions differ.
+    let reverse_reader = ReverseLineReader::new(file, start_pos, end_pos).unwrap();
+    let reassembled_reverse_reader = CriLineReassemblingReverseReader::new(reverse_reader, format);
+
     // Wrap in term reader
-    let term_reverse_reader = TermReader::new(
-        ReverseLineReader::new(file, start_pos, end_pos).unwrap(),
-        term_tx.subscribe(),
-    );
+    let term_reverse_reader = TermReader::new(ctx.clone(), reassembled_reverse_reader);
 
     // Init searcher
     let mut searcher = SearcherBuilder::new()
@@ -105,8 +155,19 @@ fn stream_backward_internal(
         .heap_limit(Some(1024 * 1024)) // TODO: Make this configurable
         .build();
 
-    // Init writer
-    let writer_fn = |chunk: Vec<u8>| process_output(chunk, sender, format, term_tx.clone());
+    // Init writer. stream_backward is a single one-shot reverse read rather than a long-lived
+    // watch loop, so there's no `CancellationToken` threaded in from the caller; a fresh one is
+    // enough to satisfy `spawn_line_processor`'s cancel-on-send-error bookkeeping.
+    let line_tx = spawn_line_processor(
+        CancellationToken::new(),
+        sender.clone(),
+        format,
+        malformed_line_policy,
+        compression,
+    );
+    let writer_fn = move |chunk: Vec<u8>| {
+        let _ = line_tx.send(chunk);
+    };
     let writer = CallbackWriter::new(writer_fn);
 
     // Init printer
@@ -115,12 +176,19 @@ fn stream_backward_internal(
     // Remove leading and trailing whitespace
     let trimmed_grep = grep.map(str::trim).filter(|grep| !grep.is_empty());
 
-    if let Some(grep) = trimmed_grep {
+    if let Some(grep_spec) = grep_spec {
+        let matcher = RegexSetMatcher::new(grep_spec, format)?;
+        let matcher = SeverityFilterMatcher::new(matcher, format, min_severity);
+        let sink = printer.sink(&matcher);
+        let _ = searcher.search_reader(&matcher, term_reverse_reader, sink);
+    } else if let Some(grep) = trimmed_grep {
         let matcher = LogFileRegexMatcher::new(grep, format).unwrap();
+        let matcher = SeverityFilterMatcher::new(matcher, format, min_severity);
         let sink = printer.sink(&matcher);
         let _ = searcher.search_reader(&matcher, term_reverse_reader, sink);
     } else {
         let matcher = PassThroughMatcher::new();
+        let matcher = SeverityFilterMatcher::new(matcher, format, min_severity);
         let sink = printer.sink(&matcher);
         let _ = searcher.search_reader(&matcher, term_reverse_reader, sink);
     }
@@ -128,6 +196,59 @@ fn stream_backward_internal(
     Ok(())
 }
 
+/// Walks the same `[start_time, stop_time]` offset window and grep/severity matcher as
+/// [`stream_backward`], but instead of forwarding matching records, floors each one's timestamp to
+/// the nearest `bucket_duration`-wide boundary and returns match counts per bucket. Buckets with no
+/// matches are absent from the result rather than present with a count of zero.
+#[allow(clippy::too_many_arguments)]
+pub async fn aggregate_backward(
+    path: &PathBuf,
+    start_time: Option<DateTime<Utc>>,
+    stop_time: Option<DateTime<Utc>>,
+    grep: Option<&str>,
+    grep_spec: Option<&GrepSpec>,
+    min_severity: Severity,
+    forced_format: Option<FileFormat>,
+    bucket_duration: Duration,
+) -> Result<BTreeMap<i64, u64>, FsWatcherError> {
+    let (tx, mut rx) = mpsc::channel(100);
+
+    // aggregate_backward is a single one-shot reverse read rather than a long-lived watch loop
+    // driven by a caller-supplied shutdown signal, so a fresh, never-cancelled token is enough.
+    let ctx = CancellationToken::new();
+
+    stream_backward_internal(
+        path,
+        start_time,
+        stop_time,
+        grep,
+        grep_spec,
+        min_severity,
+        forced_format,
+        Compression::None,
+        MalformedLinePolicy::Skip,
+        &ctx,
+        &tx,
+    )?;
+    drop(tx);
+
+    let bucket_secs = bucket_duration.as_secs().max(1) as i64;
+    let mut buckets = BTreeMap::new();
+
+    while let Some(result) = rx.recv().await {
+        // MalformedLinePolicy::Skip never sends Err over this channel; a malformed line is just
+        // dropped with a tracing warning, same as stream_backward.
+        let Ok(record) = result else { continue };
+
+        if let Some(timestamp) = record.timestamp {
+            let bucket_start = timestamp.seconds - timestamp.seconds.rem_euclid(bucket_secs);
+            *buckets.entry(bucket_start).or_insert(0u64) += 1;
+        }
+    }
+
+    Ok(buckets)
+}
+
 #[cfg(test)]
 mod test {
     use rstest::rstest;
@@ -207,7 +328,20 @@ mod test {
         // Create output channel
         let (tx, mut rx) = mpsc::channel(100);
 
-        stream_backward(&path, start_time, None, None, term_tx, tx).await;
+        stream_backward(
+            &path,
+            start_time,
+            None,
+            None,
+            None,
+            Severity::Unspecified,
+            None,
+            Compression::None,
+            MalformedLinePolicy::Skip,
+            term_tx,
+            tx,
+        )
+        .await;
 
         // Create a buffer to capture output
         let mut output = Vec::new();
@@ -248,7 +382,20 @@ mod test {
         // Create output channel
         let (tx, mut rx) = mpsc::channel(100);
 
-        stream_backward(&path, None, stop_time, None, term_tx, tx).await;
+        stream_backward(
+            &path,
+            None,
+            stop_time,
+            None,
+            None,
+            Severity::Unspecified,
+            None,
+            Compression::None,
+            MalformedLinePolicy::Skip,
+            term_tx,
+            tx,
+        )
+        .await;
 
         // Create a buffer to capture output
         let mut output = Vec::new();
@@ -298,7 +445,20 @@ mod test {
         // Create output channel
         let (tx, mut rx) = mpsc::channel(100);
 
-        stream_backward(&path, start_time, stop_time, None, term_tx, tx).await;
+        stream_backward(
+            &path,
+            start_time,
+            stop_time,
+            None,
+            None,
+            Severity::Unspecified,
+            None,
+            Compression::None,
+            MalformedLinePolicy::Skip,
+            term_tx,
+            tx,
+        )
+        .await;
 
         // Create a buffer to capture output
         let mut output = Vec::new();
@@ -321,7 +481,20 @@ mod test {
         // Create output channel
         let (tx, mut rx) = mpsc::channel(100);
 
-        stream_backward(&path, None, None, None, term_tx, tx).await;
+        stream_backward(
+            &path,
+            None,
+            None,
+            None,
+            None,
+            Severity::Unspecified,
+            None,
+            Compression::None,
+            MalformedLinePolicy::Skip,
+            term_tx,
+            tx,
+        )
+        .await;
 
         let result = rx.recv().await.unwrap();
         assert!(matches!(result, Err(_)));
@@ -330,4 +503,99 @@ mod test {
         assert_eq!(status.code(), tonic::Code::NotFound);
         assert!(status.message().contains("No such file or directory"));
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_aggregate_backward_buckets_by_duration() {
+        let path = TEST_FILE.path().to_path_buf();
+
+        // All ten lines fall within [05:40:46, 05:40:59], so a 5-second bucket splits them into
+        // three buckets: [05:40:45, 05:40:50), [05:40:50, 05:40:55), [05:40:55, 05:41:00).
+        let buckets = aggregate_backward(
+            &path,
+            None,
+            None,
+            None,
+            None,
+            Severity::Unspecified,
+            None,
+            std::time::Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+        let counts: Vec<u64> = buckets.values().copied().collect();
+        assert_eq!(counts, vec![2, 3, 5]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_aggregate_backward_omits_empty_buckets() {
+        let path = TEST_FILE.path().to_path_buf();
+
+        let buckets = aggregate_backward(
+            &path,
+            Some("2024-10-01T05:40:59.103901461Z".parse::<DateTime<Utc>>().unwrap()),
+            None,
+            None,
+            None,
+            Severity::Unspecified,
+            None,
+            std::time::Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(*buckets.values().next().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_grep_pattern_propagates_to_client_as_invalid_argument() {
+        let path = TEST_FILE.path().to_path_buf();
+        let grep_spec = GrepSpec::new(vec!["(".to_string()], vec![]);
+
+        // Create a channel for termination signal
+        let (term_tx, _term_rx) = broadcast::channel(5);
+
+        // Create output channel
+        let (tx, mut rx) = mpsc::channel(100);
+
+        stream_backward(
+            &path,
+            None,
+            None,
+            None,
+            Some(&grep_spec),
+            Severity::Unspecified,
+            None,
+            Compression::None,
+            MalformedLinePolicy::Skip,
+            term_tx,
+            tx,
+        )
+        .await;
+
+        let result = rx.recv().await.unwrap();
+        let status = result.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_aggregate_backward_rejects_invalid_grep_pattern() {
+        let path = TEST_FILE.path().to_path_buf();
+        let grep_spec = GrepSpec::new(vec!["(".to_string()], vec![]);
+
+        let result = aggregate_backward(
+            &path,
+            None,
+            None,
+            None,
+            Some(&grep_spec),
+            Severity::Unspecified,
+            None,
+            std::time::Duration::from_secs(5),
+        )
+        .await;
+
+        assert!(matches!(result, Err(FsWatcherError::InvalidGrepPattern(_))));
+    }
 }
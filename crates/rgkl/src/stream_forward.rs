@@ -12,32 +12,62 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use grep::printer::JSONBuilder;
 use grep::searcher::{MmapChoice, SearcherBuilder};
 use notify::{Config, Error, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_full::{DebounceEventResult, Debouncer, RecommendedCache, new_debouncer_opt};
 use tokio::select;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task;
 use tokio_util::sync::CancellationToken;
 use tonic::Status;
-use types::cluster_agent::{FollowFrom, LogRecord};
+use tracing::{debug, warn};
+use types::cluster_agent::{Compression, FollowFrom, LogRecord};
 
 use crate::fs_watcher_error::FsWatcherError;
-use crate::util::format::FileFormat;
-use crate::util::matcher::{LogFileRegexMatcher, PassThroughMatcher};
+use crate::shutdown_error::ShutdownError;
+use crate::util::format::{year_hint_from_mtime, FileFormat};
+use crate::util::matcher::{
+    GrepSpec, LogFileRegexMatcher, PassThroughMatcher, RegexSetMatcher, StreamFilterMatcher,
+    StreamType,
+};
 use crate::util::offset::{find_nearest_offset_since, find_nearest_offset_until};
-use crate::util::reader::{LogTrimmerReader, TermReader};
-use crate::util::writer::{process_output, CallbackWriter};
+use crate::util::reader::{CriLineReassemblingReader, LogTrimmerReader, TermReader};
+use crate::util::writer::{spawn_line_processor, CallbackWriter, MalformedLinePolicy};
 
-/// Lifecycle events emitted by stream_forward
+/// Lifecycle events emitted by stream_forward, tracking the listen loop's state transitions so a
+/// consumer can observe precise progress (and failures) instead of inferring them from the
+/// `LogRecord` stream alone.
 #[derive(Debug, Clone)]
 pub enum LifecycleEvent {
+    /// The notify watcher was registered on the file and the listen loop is about to start.
     WatcherStarted,
+    /// The historical (non-follow) read is about to begin from this byte offset in the file.
+    StreamingFromOffset(u64),
+    /// The historical read reached the end of the file; subsequent records come from live
+    /// notify events rather than the initial catch-up pass.
+    CaughtUpToEnd,
+    /// The watched file was renamed/removed out from under the watcher (kubelet-style rotation)
+    /// and a fresh file at the same path was reopened from the start.
+    FileRotated,
+    /// The watched file was truncated in place (same device and inode, smaller size) and was
+    /// reopened and resumed from the start.
+    FileTruncated,
+    /// The watcher hit an error it couldn't recover from; carries the error's display string.
+    /// Emitted immediately before the terminal status is sent on the record channel.
+    WatcherError(String),
+    /// The listen loop is stopping; carries a short human-readable reason (e.g. "server is
+    /// shutting down").
+    ShuttingDown(String),
 }
 
 /// Helper: best-effort lifecycle emission.
@@ -57,21 +87,50 @@ where
     /// Performs the grep search, meant to be used on each new log line.
     search_callback: F,
     /// Reader to get log lines from.
-    log_file_reader: BufReader<LogTrimmerReader<std::fs::File>>,
-    /// Receives the events that come from notify.
-    output_rx: Receiver<Result<Event, Error>>,
-    /// Internal notify watcher.
-    _notify_watcher: RecommendedWatcher,
+    log_file_reader: BufReader<LogTrimmerReader<CriLineReassemblingReader<std::fs::File>>>,
+    /// Receives debounced batches of events from notify.
+    output_rx: Receiver<DebounceEventResult>,
+    /// Internal notify watcher, wrapped in a debouncer so a burst of rapid events coalesces into
+    /// one batch instead of triggering a read pass per individual event.
+    _notify_watcher: Debouncer<RecommendedWatcher, RecommendedCache>,
+    /// Path being watched, kept around so a rotation/truncation can reopen it.
+    path: PathBuf,
+    /// Format of the watched file, needed to rebuild `log_file_reader` on reopen.
+    format: FileFormat,
+    /// Truncation limit, needed to rebuild `log_file_reader` on reopen.
+    truncate_at_bytes: u64,
+    /// Device and inode of the currently open file. A mismatch against a fresh `stat` means the
+    /// kubelet renamed the active log away (rotation) and created a new one in its place.
+    /// Comparing both, not just the inode, avoids a false negative if the log directory spans
+    /// more than one filesystem (e.g. an overlay upper dir and a tmpfs mount) where inode numbers
+    /// aren't guaranteed unique across devices.
+    dev: u64,
+    ino: u64,
+    /// Last known size of the currently open file. A fresh `stat` reporting a smaller size means
+    /// the file was truncated in place rather than rotated.
+    last_offset: u64,
 }
 
+/// Default debounce window applied to a single watched file's notify events when a caller
+/// doesn't have an opinion: short enough to keep follow latency unnoticeable, long enough that a
+/// high-write-rate container doesn't trigger a read pass per individual `write()` syscall.
+pub const DEFAULT_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(100);
+
+#[allow(clippy::too_many_arguments)]
 pub async fn stream_forward(
     ctx: CancellationToken,
     path: &PathBuf,
     start_time: Option<DateTime<Utc>>,
     stop_time: Option<DateTime<Utc>>,
     grep: Option<&str>,
+    grep_spec: Option<&GrepSpec>,
+    stream_filter: Option<StreamType>,
     follow_from: FollowFrom,
     truncate_at_bytes: u64,
+    debounce_interval: Duration,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
     sender: Sender<Result<LogRecord, Status>>,
 ) {
     stream_forward_with_lifecyle_events(
@@ -80,8 +139,14 @@ pub async fn stream_forward(
         start_time,
         stop_time,
         grep,
+        grep_spec,
+        stream_filter,
         follow_from,
         truncate_at_bytes,
+        debounce_interval,
+        forced_format,
+        compression,
+        malformed_line_policy,
         sender,
         None,
     )
@@ -95,8 +160,14 @@ async fn stream_forward_with_lifecyle_events(
     start_time: Option<DateTime<Utc>>,
     stop_time: Option<DateTime<Utc>>,
     grep: Option<&str>,
+    grep_spec: Option<&GrepSpec>,
+    stream_filter: Option<StreamType>,
     follow_from: FollowFrom,
     truncate_at_bytes: u64,
+    debounce_interval: Duration,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
     sender: Sender<Result<LogRecord, Status>>,
     lifecycle_tx: Option<broadcast::Sender<LifecycleEvent>>,
 ) {
@@ -106,44 +177,59 @@ async fn stream_forward_with_lifecyle_events(
         start_time,
         stop_time,
         grep,
+        grep_spec,
+        stream_filter,
         follow_from,
         truncate_at_bytes,
+        debounce_interval,
+        forced_format,
+        compression,
+        malformed_line_policy,
         &sender,
+        &lifecycle_tx,
     );
 
-    emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherStarted);
-
     match result {
         Err(fs_error) => {
+            emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherError(fs_error.to_string()));
             let _ = sender.send(Err(fs_error.into())).await;
         }
         Ok(None) => {}
-        Ok(Some(watcher)) => listen_for_changes(ctx.clone(), watcher, sender.clone()).await,
+        Ok(Some(watcher)) => {
+            listen_for_changes(ctx.clone(), watcher, sender.clone(), lifecycle_tx).await;
+        }
     }
 }
 
 type ResultOption<T, E> = Result<Option<T>, E>;
 
+#[allow(clippy::too_many_arguments)]
 fn setup_fs_watcher<'a>(
     ctx: CancellationToken,
     path: &PathBuf,
     start_time: Option<DateTime<Utc>>,
     stop_time: Option<DateTime<Utc>>,
     grep: Option<&'a str>,
+    grep_spec: Option<&'a GrepSpec>,
+    stream_filter: Option<StreamType>,
     follow_from: FollowFrom,
     truncate_at_bytes: u64,
+    debounce_interval: Duration,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
     sender: &'a Sender<Result<LogRecord, Status>>,
+    lifecycle_tx: &Option<broadcast::Sender<LifecycleEvent>>,
 ) -> ResultOption<FsWatcher<impl FnMut(&[u8]) + use<'a>>, FsWatcherError> {
     let mut file = File::open(path)?;
 
     let max_offset = file.metadata()?.len();
 
-    // Determine format based on filename
-    let format = if path.to_string_lossy().ends_with("-json.log") {
-        FileFormat::Docker
-    } else {
-        FileFormat::CRI
-    };
+    // Anchors year-less formats (klog, classic syslog) to this file's own last-modified year,
+    // since neither of those headers carries one.
+    let year_hint = year_hint_from_mtime(&file);
+
+    let format = FileFormat::resolve(path, &file, forced_format, year_hint)?;
 
     // Get start pos
     let mut start_pos: u64 = 0;
@@ -151,20 +237,24 @@ fn setup_fs_watcher<'a>(
         // When following from the end, start at the end of the file
         start_pos = max_offset;
     } else if let Some(start_time) = start_time {
-        if let Some(offset) = find_nearest_offset_since(&file, start_time, 0, max_offset, format)? {
+        if let Some(offset) =
+            find_nearest_offset_since(&file, start_time, 0, max_offset, format, year_hint, true)?
+        {
             start_pos = offset.byte_offset;
         } else {
             return Ok(None); // No records, exit early
         }
     }
 
+    emit_lifecycle(lifecycle_tx, LifecycleEvent::StreamingFromOffset(start_pos));
+
     // Calculate the length to take
     let mut take_length: Option<u64> = None;
     if follow_from != FollowFrom::End {
         if let Some(stop_time) = stop_time {
-            if let Some(offset) =
-                find_nearest_offset_until(&file, stop_time, start_pos, max_offset, format)?
-            {
+            if let Some(offset) = find_nearest_offset_until(
+                &file, stop_time, start_pos, max_offset, format, year_hint, true,
+            )? {
                 take_length = Some(offset.byte_offset + offset.line_length - start_pos);
             } else {
                 return Ok(None); // No records, exit early
@@ -181,10 +271,18 @@ fn setup_fs_watcher<'a>(
         None => Box::new(file),
     };
 
-    // Wrap with truncation reader
+    // Reassemble kubelet-split CRI lines ahead of both truncation and the grep searcher, so a
+    // pattern spanning the split point can match and truncate_at_bytes sees the full message.
+    let reader: Box<dyn Read> = Box::new(CriLineReassemblingReader::new(reader, format));
+
+    // Wrap with truncation reader. Also wired up to `ctx` so a cancellation arriving mid-scan of
+    // a pathologically long or newline-free line is noticed without waiting for the outer
+    // `TermReader` to get a chance to check it between whole-line reads.
     let reader: Box<dyn Read> = match truncate_at_bytes {
         0 => reader,
-        limit => Box::new(LogTrimmerReader::new(reader, format, limit)),
+        limit => Box::new(
+            LogTrimmerReader::new(reader, format, limit).with_cancellation_token(ctx.clone()),
+        ),
     };
 
     // Wrap with term reader
@@ -197,9 +295,15 @@ fn setup_fs_watcher<'a>(
         .multi_line(false)
         .build();
 
-    let ctx_copy = ctx.clone();
+    let line_tx = spawn_line_processor(
+        ctx.clone(),
+        sender.clone(),
+        format,
+        malformed_line_policy,
+        compression,
+    );
     let writer_fn = move |chunk: Vec<u8>| {
-        process_output(ctx_copy.clone(), chunk, sender, format);
+        let _ = line_tx.send(chunk);
     };
     let writer = CallbackWriter::new(writer_fn);
     let mut printer = JSONBuilder::new().build(writer);
@@ -207,12 +311,25 @@ fn setup_fs_watcher<'a>(
     // Remove leading and trailing whitespace
     let trimmed_grep = grep.map(str::trim).filter(|grep| !grep.is_empty());
 
-    if let Some(grep) = trimmed_grep {
+    // Built once and cloned into both the one-shot search below and `search_slice` (rather than
+    // reconstructed from `grep_spec` at each use, including once per live notify event) so a bad
+    // include/exclude pattern surfaces as an error here instead of an `.unwrap()` panic later.
+    let grep_set_matcher = grep_spec
+        .map(|spec| RegexSetMatcher::new(spec, format))
+        .transpose()?;
+
+    if let Some(matcher) = grep_set_matcher.clone() {
+        let matcher = StreamFilterMatcher::new(matcher, format, stream_filter);
+        let sink = printer.sink(&matcher);
+        let _ = searcher.search_reader(&matcher, reader, sink);
+    } else if let Some(grep) = trimmed_grep {
         let matcher = LogFileRegexMatcher::new(grep, format).unwrap();
+        let matcher = StreamFilterMatcher::new(matcher, format, stream_filter);
         let sink = printer.sink(&matcher);
         let _ = searcher.search_reader(&matcher, reader, sink);
     } else {
         let matcher = PassThroughMatcher::new();
+        let matcher = StreamFilterMatcher::new(matcher, format, stream_filter);
         let sink = printer.sink(&matcher);
         let _ = searcher.search_reader(&matcher, reader, sink);
     }
@@ -229,33 +346,56 @@ fn setup_fs_watcher<'a>(
         return Ok(None);
     }
 
+    // The one-shot historical search above has already read through every record up to
+    // `max_offset`; everything from here on comes from live notify events instead.
+    emit_lifecycle(lifecycle_tx, LifecycleEvent::CaughtUpToEnd);
+
     let search_slice = move |input_str: &[u8]| {
-        if let Some(grep) = trimmed_grep {
+        if let Some(matcher) = grep_set_matcher.clone() {
+            let matcher = StreamFilterMatcher::new(matcher, format, stream_filter);
+            let sink = printer.sink(&matcher);
+            let _ = searcher.search_slice(&matcher, input_str, sink);
+        } else if let Some(grep) = trimmed_grep {
             let matcher = LogFileRegexMatcher::new(grep, format).unwrap();
+            let matcher = StreamFilterMatcher::new(matcher, format, stream_filter);
             let sink = printer.sink(&matcher);
             let _ = searcher.search_slice(&matcher, input_str, sink);
         } else {
             let matcher = PassThroughMatcher::new();
+            let matcher = StreamFilterMatcher::new(matcher, format, stream_filter);
             let sink = printer.sink(&matcher);
             let _ = searcher.search_slice(&matcher, input_str, sink);
         }
     };
 
-    // Set up watcher
+    // Set up watcher. Debounced so a burst of rapid notify events (a high-write-rate container
+    // writing many times a second) coalesces into one batch and one read pass instead of one
+    // read per individual write; `debounce_interval` bounds how long a batch can be held open.
     let (notify_tx, notify_rx) = mpsc::channel(100);
 
-    let mut watcher = RecommendedWatcher::new(
-        move |result: Result<Event, Error>| {
+    let mut watcher = new_debouncer_opt::<_, RecommendedWatcher, _>(
+        debounce_interval,
+        None,
+        move |result: DebounceEventResult| {
             let _ = notify_tx.blocking_send(result);
         },
+        RecommendedCache::new(),
         Config::default(),
     )?;
 
     watcher.watch(path, RecursiveMode::NonRecursive)?;
 
+    emit_lifecycle(lifecycle_tx, LifecycleEvent::WatcherStarted);
+
     // Open file
     let mut reader = File::open(path)?;
-    reader.seek(SeekFrom::End(0))?;
+    let opened_metadata = reader.metadata()?;
+    let dev = opened_metadata.dev();
+    let ino = opened_metadata.ino();
+    let last_offset = reader.seek(SeekFrom::End(0))?;
+
+    // Reassemble kubelet-split CRI lines ahead of truncation, same as the one-shot path above.
+    let reader = CriLineReassemblingReader::new(reader, format);
 
     // Wrap with truncation reader
     let reader = LogTrimmerReader::new(reader, format, truncate_at_bytes);
@@ -279,6 +419,12 @@ fn setup_fs_watcher<'a>(
         log_file_reader: reader,
         _notify_watcher: watcher,
         output_rx: notify_rx,
+        path: path.clone(),
+        format,
+        truncate_at_bytes,
+        dev,
+        ino,
+        last_offset,
     }))
 }
 
@@ -288,13 +434,17 @@ async fn listen_for_changes(
     ctx: CancellationToken,
     mut fs_watcher: FsWatcher<impl FnMut(&[u8])>,
     sender: Sender<Result<LogRecord, Status>>,
+    lifecycle_tx: Option<broadcast::Sender<LifecycleEvent>>,
 ) {
     'outer: loop {
         select! {
             ev = fs_watcher.output_rx.recv() => {
                 match ev {
-                    Some(Ok(event)) => {
-                        if let EventKind::Modify(_) = event.kind {
+                    Some(Ok(events)) => {
+                        // A whole debounced batch is handled as one unit: if any event in it was
+                        // a Modify, drain every newly-appended line once rather than once per
+                        // individual event the batch coalesced.
+                        if events.iter().any(|event| matches!(event.kind, EventKind::Modify(_))) {
                             for line in (&mut fs_watcher.log_file_reader).lines() {
                                 if ctx.is_cancelled() {
                                     break 'outer;
@@ -304,34 +454,402 @@ async fn listen_for_changes(
                                     Ok(l) => {
                                         (fs_watcher.search_callback)(l.as_bytes());
                                     },
+                                    Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                                        let shutdown_error = ShutdownError::FileRemoved { path: fs_watcher.path.clone() };
+                                        emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherError(shutdown_error.to_string()));
+                                        let _ = sender.send(Err(shutdown_error.into())).await;
+                                        return;
+                                    }
                                     Err(e) => {
-                                        let _ = sender.send(Err(Status::from_error(Box::new(e)))).await;
+                                        let shutdown_error = ShutdownError::WatcherFailed { source: Box::new(e) };
+                                        emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherError(shutdown_error.to_string()));
+                                        let _ = sender.send(Err(shutdown_error.into())).await;
                                         return;
                                     }
                                 }
                             }
                         }
+
+                        // Reconcile against the file on disk on every batch, not just Modify:
+                        // a rotation typically surfaces as Remove/Create rather than Modify.
+                        if let Err(e) = reopen_on_rotation_or_truncation(&mut fs_watcher, &lifecycle_tx) {
+                            let shutdown_error = if e.kind() == io::ErrorKind::NotFound {
+                                ShutdownError::FileRemoved { path: fs_watcher.path.clone() }
+                            } else {
+                                ShutdownError::WatcherFailed { source: Box::new(e) }
+                            };
+                            emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherError(shutdown_error.to_string()));
+                            let _ = sender.send(Err(shutdown_error.into())).await;
+                            return;
+                        }
                     },
-                    Some(Err(e)) => {
-                        let _ = sender.send(Err(Status::from(FsWatcherError::Watch(e)))).await;
+                    Some(Err(errors)) => {
+                        let source = errors
+                            .into_iter()
+                            .next()
+                            .map_or_else(
+                                || Box::new(io::Error::other("watcher reported an empty error batch")) as Box<dyn std::error::Error + Send + Sync>,
+                                |e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>,
+                            );
+                        let shutdown_error = ShutdownError::WatcherFailed { source };
+                        emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherError(shutdown_error.to_string()));
+                        let _ = sender.send(Err(shutdown_error.into())).await;
                         return;
                     }
                     None => {
-                        let _ = sender.send(Err(Status::new(tonic::Code::Unknown, "Notify channel closed."))).await;
+                        let shutdown_error = ShutdownError::WatcherFailed {
+                            source: Box::new(io::Error::other("notify channel closed")),
+                        };
+                        emit_lifecycle(&lifecycle_tx, LifecycleEvent::WatcherError(shutdown_error.to_string()));
+                        let _ = sender.send(Err(shutdown_error.into())).await;
                         return;
                     }
                 }
             },
             _ = ctx.cancelled() => {
-                // Send gRPC UNAVAILABLE error to indicate server shutdown
-                let shutdown_status = Status::new(tonic::Code::Unavailable, "Server is shutting down");
-                let _ = sender.send(Err(shutdown_status)).await;
+                // A P fragment that never saw its terminating F record would otherwise be lost
+                // silently; flush it through the matcher like any other line before shutting down.
+                if let Some(line) = fs_watcher
+                    .log_file_reader
+                    .get_mut()
+                    .get_inner_mut()
+                    .flush_pending()
+                {
+                    (fs_watcher.search_callback)(&line);
+                }
+
+                let shutdown_error = ShutdownError::GracefulShutdown;
+                emit_lifecycle(&lifecycle_tx, LifecycleEvent::ShuttingDown(shutdown_error.to_string()));
+                let _ = sender.send(Err(shutdown_error.into())).await;
                 break 'outer;
             },
         }
     }
 }
 
+/// Reconciles `fs_watcher` against the current state of the file on disk, the way a file watcher
+/// like watchexec does after a notify event: if the device or inode changed, the kubelet renamed
+/// the active log away and created a new one in its place, so the old handle (already drained by
+/// the caller) is dropped and a fresh one is opened from the start. If both are unchanged but the
+/// size dropped below what was last observed, the file was truncated in place, so the same path
+/// is reopened and resumed from the start. Emits `LifecycleEvent::FileRotated` or
+/// `LifecycleEvent::FileTruncated` accordingly.
+fn reopen_on_rotation_or_truncation<F: FnMut(&[u8])>(
+    fs_watcher: &mut FsWatcher<F>,
+    lifecycle_tx: &Option<broadcast::Sender<LifecycleEvent>>,
+) -> io::Result<()> {
+    // The file may momentarily not exist between the old one being removed and the new one
+    // being created; treat that as "nothing to reconcile yet" rather than a fatal error.
+    let metadata = match std::fs::metadata(&fs_watcher.path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let current_dev = metadata.dev();
+    let current_ino = metadata.ino();
+    let current_size = metadata.len();
+
+    let rotated = current_dev != fs_watcher.dev || current_ino != fs_watcher.ino;
+    let truncated = !rotated && current_size < fs_watcher.last_offset;
+
+    if rotated || truncated {
+        let file = File::open(&fs_watcher.path)?;
+        fs_watcher.log_file_reader = BufReader::new(LogTrimmerReader::new(
+            CriLineReassemblingReader::new(file, fs_watcher.format),
+            fs_watcher.format,
+            fs_watcher.truncate_at_bytes,
+        ));
+        fs_watcher.dev = current_dev;
+        fs_watcher.ino = current_ino;
+        fs_watcher.last_offset = 0;
+        if rotated {
+            emit_lifecycle(lifecycle_tx, LifecycleEvent::FileRotated);
+        } else {
+            emit_lifecycle(lifecycle_tx, LifecycleEvent::FileTruncated);
+        }
+    } else {
+        fs_watcher.last_offset = current_size;
+    }
+
+    Ok(())
+}
+
+/// Whether `path` looks like a container log file `stream_forward_dir` should watch, rather than
+/// some other entry the container runtime or an operator happens to drop in the same directory
+/// (a `.tmp` file mid-write, a `.gz` segment already handled elsewhere). Both the Docker
+/// (`*-json.log`) and CRI (`*.log`) naming conventions end in `.log`.
+fn is_log_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "log")
+}
+
+/// Best-effort soft-limit raise on the number of open file descriptors, the way `ulimit -n`
+/// would bump it for an interactive shell. `stream_forward_dir` can have one file handle open per
+/// watched container, and the default soft limit (1024 on most Linux distros and macOS) is easy
+/// to exhaust on a busy node long before the kernel's hard limit is reached.
+///
+/// Returns the soft limit in effect after the call (unchanged from before if raising it failed or
+/// wasn't needed). A failure here isn't fatal; the caller logs it and keeps going with whatever
+/// limit the process already had.
+fn raise_fd_limit() -> io::Result<u64> {
+    let mut limits = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+
+    // SAFETY: `limits` is a valid, fully-initialized `rlimit` struct and `RLIMIT_NOFILE` is a
+    // well-known resource constant; this is the standard getrlimit FFI call.
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limits) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // macOS reports RLIM_INFINITY as the hard limit but refuses to actually raise the soft limit
+    // past OPEN_MAX, so clamp to that instead of the (unusable) reported hard limit.
+    #[cfg(target_os = "macos")]
+    let target = limits.rlim_max.min(libc::OPEN_MAX as u64);
+    #[cfg(not(target_os = "macos"))]
+    let target = limits.rlim_max;
+
+    if target <= limits.rlim_cur {
+        return Ok(limits.rlim_cur);
+    }
+
+    limits.rlim_cur = target;
+
+    // SAFETY: same as above; `limits` now holds the clamped target we're requesting.
+    if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limits) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(target)
+}
+
+/// Recursively collects every log file already present under `directory`, the way
+/// `log_metadata_watcher::find_log_files` walks a directory tree up front before handing it to
+/// notify. Symlinked directories are followed but their canonical target is tracked in `visited`
+/// so a symlink cycle can't make the walk loop forever.
+fn find_existing_log_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut result = Vec::new();
+    let mut dirs_to_scan = VecDeque::from([directory.to_path_buf()]);
+    let mut visited = std::collections::HashSet::new();
+
+    while let Some(dir) = dirs_to_scan.pop_front() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                if let Ok(canonical) = std::fs::canonicalize(&path) {
+                    if !visited.insert(canonical) {
+                        continue;
+                    }
+                }
+                dirs_to_scan.push_back(path);
+            } else if is_log_file(&path) {
+                result.push(path);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Per-file state `stream_forward_dir` needs to tear a watch down again: the token that cancels
+/// just that file's `stream_forward` task.
+type FileTasks = HashMap<PathBuf, CancellationToken>;
+
+/// Spawns a `stream_forward` task for `path`, tags every `LogRecord` it emits with `path` so a
+/// consumer reading the multiplexed `sender` can tell which file it came from, and records the
+/// per-file cancellation token in `tasks` so a later `EventKind::Remove` can tear it down.
+#[allow(clippy::too_many_arguments)]
+fn spawn_file_task(
+    ctx: &CancellationToken,
+    tasks: &mut FileTasks,
+    path: PathBuf,
+    start_time: Option<DateTime<Utc>>,
+    stop_time: Option<DateTime<Utc>>,
+    grep: Option<String>,
+    grep_spec: Option<GrepSpec>,
+    stream_filter: Option<StreamType>,
+    follow_from: FollowFrom,
+    truncate_at_bytes: u64,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
+    sender: &Sender<Result<LogRecord, Status>>,
+) {
+    let file_ctx = ctx.child_token();
+    let source_path = path.to_string_lossy().into_owned();
+    let out_sender = sender.clone();
+    let (file_tx, mut file_rx) = mpsc::channel(100);
+
+    task::spawn({
+        let path = path.clone();
+        let file_ctx = file_ctx.clone();
+        async move {
+            stream_forward(
+                file_ctx,
+                &path,
+                start_time,
+                stop_time,
+                grep.as_deref(),
+                grep_spec.as_ref(),
+                stream_filter,
+                follow_from,
+                truncate_at_bytes,
+                DEFAULT_DEBOUNCE_INTERVAL,
+                forced_format,
+                compression,
+                malformed_line_policy,
+                file_tx,
+            )
+            .await;
+        }
+    });
+
+    task::spawn(async move {
+        while let Some(result) = file_rx.recv().await {
+            let tagged = result.map(|mut record| {
+                record.source_path = source_path.clone();
+                record
+            });
+
+            if out_sender.send(tagged).await.is_err() {
+                debug!("Channel closed from client.");
+                break;
+            }
+        }
+    });
+
+    tasks.insert(path, file_ctx);
+}
+
+/// Watches an entire container-log directory (e.g. all `*-json.log`/CRI files the kubelet lays
+/// down for a node's pods) rather than a single file, spawning a per-file `stream_forward` task
+/// as files appear and tearing it down again once the file is removed, all multiplexed into one
+/// `sender`. Each emitted `LogRecord.source_path` names the file it came from, so a caller can
+/// demultiplex the combined stream back into one per-container view.
+///
+/// `directory` is watched with `RecursiveMode::Recursive`, for runtimes (or this function's own
+/// startup scan) that nest log files under per-pod/per-container subdirectories.
+///
+/// Before watching, this makes a best-effort attempt to raise the process's open file descriptor
+/// soft limit (see [`raise_fd_limit`]), since watching hundreds of files concurrently can
+/// otherwise exhaust the conservative default on many platforms.
+#[allow(clippy::too_many_arguments)]
+pub async fn stream_forward_dir(
+    ctx: CancellationToken,
+    directory: &PathBuf,
+    start_time: Option<DateTime<Utc>>,
+    stop_time: Option<DateTime<Utc>>,
+    grep: Option<&str>,
+    grep_spec: Option<&GrepSpec>,
+    stream_filter: Option<StreamType>,
+    follow_from: FollowFrom,
+    truncate_at_bytes: u64,
+    forced_format: Option<FileFormat>,
+    compression: Compression,
+    malformed_line_policy: MalformedLinePolicy,
+    sender: Sender<Result<LogRecord, Status>>,
+) -> Result<(), FsWatcherError> {
+    if !directory.is_dir() {
+        return Err(FsWatcherError::DirNotFound(
+            directory.to_string_lossy().into_owned(),
+        ));
+    }
+
+    match raise_fd_limit() {
+        Ok(limit) => debug!("raised open file descriptor soft limit to {limit}"),
+        Err(error) => {
+            warn!("failed to raise open file descriptor limit, continuing with the existing one: {error}")
+        }
+    }
+
+    let (notify_tx, mut notify_rx) = mpsc::channel(100);
+
+    let mut watcher = RecommendedWatcher::new(
+        move |result: Result<Event, Error>| {
+            let _ = notify_tx.blocking_send(result);
+        },
+        Config::default(),
+    )?;
+
+    watcher.watch(directory, RecursiveMode::Recursive)?;
+
+    let mut tasks: FileTasks = HashMap::new();
+
+    for path in find_existing_log_files(directory)? {
+        spawn_file_task(
+            &ctx,
+            &mut tasks,
+            path,
+            start_time,
+            stop_time,
+            grep.map(str::to_owned),
+            grep_spec.cloned(),
+            stream_filter,
+            follow_from,
+            truncate_at_bytes,
+            forced_format,
+            compression,
+            malformed_line_policy.clone(),
+            &sender,
+        );
+    }
+
+    loop {
+        select! {
+            event = notify_rx.recv() => {
+                match event {
+                    Some(Ok(event)) => match event.kind {
+                        EventKind::Create(_) => {
+                            for path in event.paths {
+                                if is_log_file(&path) && !tasks.contains_key(&path) {
+                                    spawn_file_task(
+                                        &ctx,
+                                        &mut tasks,
+                                        path,
+                                        start_time,
+                                        stop_time,
+                                        grep.map(str::to_owned),
+                                        grep_spec.cloned(),
+                                        stream_filter,
+                                        follow_from,
+                                        truncate_at_bytes,
+                                        forced_format,
+                                        compression,
+                                        malformed_line_policy.clone(),
+                                        &sender,
+                                    );
+                                }
+                            }
+                        }
+                        EventKind::Remove(_) => {
+                            for path in event.paths {
+                                if let Some(file_ctx) = tasks.remove(&path) {
+                                    file_ctx.cancel();
+                                }
+                            }
+                        }
+                        _ => {}
+                    },
+                    Some(Err(error)) => {
+                        let _ = sender.send(Err(Status::from(FsWatcherError::Watch(error)))).await;
+                        break;
+                    }
+                    None => break,
+                }
+            },
+            _ = ctx.cancelled() => {
+                for (_, file_ctx) in tasks.drain() {
+                    file_ctx.cancel();
+                }
+                break;
+            },
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use std::{io::Write, path::Path, sync::LazyLock};
@@ -394,7 +912,7 @@ mod test {
                 Ok(record) => Some(record.message),
                 Err(status)
                     if status.code() == tonic::Code::Unavailable
-                        && status.message() == "Server is shutting down" =>
+                        && status.message() == "server is shutting down" =>
                 {
                     None
                 } // Filter out shutdown errors
@@ -445,8 +963,14 @@ mod test {
             start_time,
             None,             // No stop time
             None,             // No grep filter
+            None,             // No grep spec
+            None,             // No stream filter
             FollowFrom::Noop, // Don't follow
             0,                // No truncation
+            DEFAULT_DEBOUNCE_INTERVAL,
+            None, // No forced format
+            Compression::None, // No compression
+            MalformedLinePolicy::Skip,
             tx,
         )
         .await;
@@ -494,8 +1018,14 @@ mod test {
             None, // No start time
             stop_time,
             None,             // No grep filter
+            None,             // No grep spec
+            None,             // No stream filter
             FollowFrom::Noop, // Don't follow
             0,                // No truncation
+            DEFAULT_DEBOUNCE_INTERVAL,
+            None, // No forced format
+            Compression::None, // No compression
+            MalformedLinePolicy::Skip,
             tx,
         )
         .await;
@@ -552,8 +1082,14 @@ mod test {
             start_time,
             stop_time,
             None,             // No grep filter
+            None,             // No grep spec
+            None,             // No stream filter
             FollowFrom::Noop, // Don't follow
             0,                // No truncation
+            DEFAULT_DEBOUNCE_INTERVAL,
+            None, // No forced format
+            Compression::None, // No compression
+            MalformedLinePolicy::Skip,
             tx,
         )
         .await;
@@ -616,8 +1152,14 @@ mod test {
                 start_time,
                 None, // No stop time
                 None, // No grep filter
+                None, // No grep spec
+                None, // No stream filter
                 follow_from,
                 0, // No truncation
+                DEFAULT_DEBOUNCE_INTERVAL,
+                None, // No forced format
+                Compression::None, // No compression
+                MalformedLinePolicy::Skip,
                 tx,
                 Some(lifecycle_tx_clone),
             )
@@ -663,8 +1205,14 @@ mod test {
             None,
             None,
             None,             // No grep filter
+            None,             // No grep spec
+            None,             // No stream filter
             FollowFrom::Noop, // Don't follow
             0,                // No truncation
+            DEFAULT_DEBOUNCE_INTERVAL,
+            None, // No forced format
+            Compression::None, // No compression
+            MalformedLinePolicy::Skip,
             tx,
         )
         .await;
@@ -677,6 +1225,38 @@ mod test {
         assert!(status.message().contains("No such file or directory"));
     }
 
+    #[tokio::test]
+    async fn test_invalid_grep_pattern_propagates_to_client_as_invalid_argument() {
+        let path = TEST_FILE.path().to_path_buf();
+        let grep_spec = GrepSpec::new(vec!["(".to_string()], vec![]);
+
+        // Create output channel
+        let (tx, mut rx) = mpsc::channel(100);
+
+        // Call run method
+        stream_forward(
+            CancellationToken::new(),
+            &path,
+            None,
+            None,
+            None,                 // No grep filter
+            Some(&grep_spec),     // Invalid regex
+            None,                 // No stream filter
+            FollowFrom::Noop,     // Don't follow
+            0,                    // No truncation
+            DEFAULT_DEBOUNCE_INTERVAL,
+            None, // No forced format
+            Compression::None, // No compression
+            MalformedLinePolicy::Skip,
+            tx,
+        )
+        .await;
+
+        let result = rx.recv().await.unwrap();
+        let status = result.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
     #[tokio::test]
     async fn test_shutdown_error_sent_on_termination() {
         // Prepare a fresh temp file and paths
@@ -703,8 +1283,14 @@ mod test {
                 None,            // No start time
                 None,            // No stop time
                 None,            // No grep filter
+                None,            // No grep spec
+                None,            // No stream filter
                 FollowFrom::End, // Enter listen loop immediately
                 0,               // No truncation
+                DEFAULT_DEBOUNCE_INTERVAL,
+                None, // No forced format
+                Compression::None, // No compression
+                MalformedLinePolicy::Skip,
                 tx,
                 Some(lifecycle_tx_clone),
             )
@@ -723,9 +1309,164 @@ mod test {
         let last = rx.recv().await.expect("should forward shutdown error");
         let status = last.unwrap_err();
         assert_eq!(status.code(), tonic::Code::Unavailable);
-        assert_eq!(status.message(), "Server is shutting down");
+        assert_eq!(status.message(), "server is shutting down");
 
         // Channel should close after sending the shutdown error
         assert!(rx.recv().await.is_none());
     }
+
+    // Test rotation (rename-and-recreate) and in-place truncation mid-follow
+    #[tokio::test(flavor = "multi_thread")]
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    async fn test_log_rotation_reopens_file(#[case] rotate: bool) {
+        let test_file = create_test_file();
+        let path = test_file.path().to_path_buf();
+
+        let ctx = CancellationToken::new();
+        let (tx, mut rx) = mpsc::channel(100);
+        let (lifecycle_tx, mut lifecycle_rx) = broadcast::channel(4);
+
+        let ctx_clone = ctx.clone();
+        let path_clone = path.clone();
+        let lifecycle_tx_clone = lifecycle_tx.clone();
+
+        task::spawn(async move {
+            stream_forward_with_lifecyle_events(
+                ctx_clone,
+                &path_clone,
+                None, // No start time
+                None, // No stop time
+                None, // No grep filter
+                None, // No grep spec
+                None, // No stream filter
+                FollowFrom::End, // Enter listen loop immediately
+                0, // No truncation
+                DEFAULT_DEBOUNCE_INTERVAL,
+                None, // No forced format
+                Compression::None, // No compression
+                MalformedLinePolicy::Skip,
+                tx,
+                Some(lifecycle_tx_clone),
+            )
+            .await;
+        });
+
+        // Wait for WatcherStartedEvent
+        while !matches!(
+            lifecycle_rx.recv().await,
+            Ok(LifecycleEvent::WatcherStarted)
+        ) {}
+
+        let new_line = "2024-10-01T05:42:00.000000000Z stdout F linenum 14";
+        let rotated_path = path.with_extension("rotated");
+
+        if rotate {
+            // Simulate kubelet-style rotation: rename the active file away, then create a fresh
+            // file at the original path.
+            std::fs::rename(&path, &rotated_path).expect("Failed to rotate test file");
+
+            let mut new_file = std::fs::File::create(&path).expect("Failed to create new file");
+            writeln!(new_file, "{new_line}").expect("Failed to write to new file");
+            new_file.sync_all().expect("Failed to sync new file");
+        } else {
+            // Simulate in-place truncation: same inode, shorter file.
+            let mut truncated_file = std::fs::OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(&path)
+                .expect("Failed to truncate test file");
+            writeln!(truncated_file, "{new_line}").expect("Failed to write to truncated file");
+            truncated_file.sync_all().expect("Failed to sync truncated file");
+        }
+
+        // Wait for the matching reopen event
+        if rotate {
+            while !matches!(lifecycle_rx.recv().await, Ok(LifecycleEvent::FileRotated)) {}
+        } else {
+            while !matches!(lifecycle_rx.recv().await, Ok(LifecycleEvent::FileTruncated)) {}
+        }
+
+        ctx.cancel();
+
+        let mut output = Vec::new();
+        while let Some(record) = rx.recv().await {
+            output.push(record);
+        }
+
+        let messages: Vec<String> = output
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|record| record.message)
+            .collect();
+
+        assert!(messages.iter().any(|message| message == "linenum 14"));
+
+        if rotate {
+            let _ = std::fs::remove_file(&rotated_path);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_stream_forward_dir_tags_source_path_and_picks_up_new_files() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let existing_path = dir.path().join("existing.log");
+        std::fs::write(
+            &existing_path,
+            "2024-10-01T05:40:46.960135302Z stdout F from existing\n",
+        )
+        .expect("Failed to write existing log file");
+
+        let ctx = CancellationToken::new();
+        let (tx, mut rx) = mpsc::channel(100);
+        let dir_path = dir.path().to_path_buf();
+        let ctx_clone = ctx.clone();
+
+        task::spawn(async move {
+            let _ = stream_forward_dir(
+                ctx_clone,
+                &dir_path,
+                None,                // No start time
+                None,                // No stop time
+                None,                // No grep filter
+                None,                // No grep spec
+                None,                // No stream filter
+                FollowFrom::Default, // Read existing content, then follow
+                0,                   // No truncation
+                None,                // No forced format
+                Compression::None,   // No compression
+                MalformedLinePolicy::Skip,
+                tx,
+            )
+            .await;
+        });
+
+        let first = rx
+            .recv()
+            .await
+            .expect("should receive the pre-existing file's line")
+            .expect("record should not be an error");
+        assert_eq!(first.message, "from existing");
+        assert!(first.source_path.ends_with("existing.log"));
+
+        // By now the directory watch is already established (it's set up before any per-file
+        // task, including the one that just produced `first`), so the new file's Create event
+        // can't be missed.
+        let new_path = dir.path().join("new.log");
+        let mut new_file = std::fs::File::create(&new_path).expect("Failed to create new file");
+        writeln!(new_file, "2024-10-01T05:41:00.103901462Z stdout F from new")
+            .expect("Failed to write to new file");
+        new_file.sync_all().expect("Failed to sync new file");
+
+        let second = rx
+            .recv()
+            .await
+            .expect("should receive the newly created file's line")
+            .expect("record should not be an error");
+        assert_eq!(second.message, "from new");
+        assert!(second.source_path.ends_with("new.log"));
+
+        ctx.cancel();
+    }
 }
@@ -0,0 +1,101 @@
+// Copyright 2024-2025 Andres Morey
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, Read, Write};
+
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+
+pub use types::cluster_agent::Compression;
+
+/// Compresses `message` with `codec` as a single self-contained streaming frame: the codec writes
+/// its output incrementally rather than building the compressed form in one shot, so a message of
+/// any size is handled without a second buffer the size of the compressed output. The frame is
+/// independent of any other record, so `decode` never needs neighboring records to make progress.
+pub fn encode(codec: Compression, message: &[u8]) -> io::Result<Vec<u8>> {
+    match codec {
+        Compression::None => Ok(message.to_vec()),
+        Compression::Lz4 => {
+            let mut encoder = FrameEncoder::new(Vec::new());
+            encoder.write_all(message)?;
+            encoder.finish().map_err(io::Error::other)
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::stream::Encoder::new(Vec::new(), 0)?;
+            encoder.write_all(message)?;
+            encoder.finish()
+        }
+    }
+}
+
+/// Reverses [`encode`], streaming the compressed bytes back out incrementally.
+pub fn decode(codec: Compression, compressed: &[u8]) -> io::Result<Vec<u8>> {
+    match codec {
+        Compression::None => Ok(compressed.to_vec()),
+        Compression::Lz4 => {
+            let mut decoder = FrameDecoder::new(compressed);
+            let mut message = Vec::new();
+            decoder.read_to_end(&mut message)?;
+            Ok(message)
+        }
+        Compression::Zstd => {
+            let mut decoder = zstd::stream::Decoder::new(compressed)?;
+            let mut message = Vec::new();
+            decoder.read_to_end(&mut message)?;
+            Ok(message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, Compression};
+
+    #[test]
+    fn none_round_trips_without_modifying_bytes() {
+        let message = b"linenum 1";
+        let compressed = encode(Compression::None, message).unwrap();
+        assert_eq!(compressed, message);
+        assert_eq!(decode(Compression::None, &compressed).unwrap(), message);
+    }
+
+    #[test]
+    fn lz4_round_trips_arbitrary_message() {
+        let message = "a very repetitive log line ".repeat(100);
+        let compressed = encode(Compression::Lz4, message.as_bytes()).unwrap();
+        assert!(compressed.len() < message.len());
+        assert_eq!(
+            decode(Compression::Lz4, &compressed).unwrap(),
+            message.as_bytes()
+        );
+    }
+
+    #[test]
+    fn zstd_round_trips_arbitrary_message() {
+        let message = "a very repetitive log line ".repeat(100);
+        let compressed = encode(Compression::Zstd, message.as_bytes()).unwrap();
+        assert!(compressed.len() < message.len());
+        assert_eq!(
+            decode(Compression::Zstd, &compressed).unwrap(),
+            message.as_bytes()
+        );
+    }
+
+    #[test]
+    fn empty_message_round_trips() {
+        for codec in [Compression::None, Compression::Lz4, Compression::Zstd] {
+            let compressed = encode(codec, b"").unwrap();
+            assert_eq!(decode(codec, &compressed).unwrap(), b"");
+        }
+    }
+}
@@ -0,0 +1,433 @@
+// Copyright 2024-2025 Andres Morey
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use types::cluster_agent::LogFormat;
+
+/// On-disk log format a source file is written in. `Docker` and `CRI` are the two formats the
+/// kubelet itself produces; the rest let this agent be pointed at non-kubelet sources (e.g. a
+/// journald-exported unit log, or a klog/glog-style node component log) via
+/// `LogRecordsStreamRequest.forced_format`, or at whatever [`FileFormat::detect`] sniffs out when
+/// the filename gives no hint.
+///
+/// `JournaldExport`'s timestamp can be located and used for `find_nearest_offset_since/until`
+/// seeking, but its record body spans a blank-line-terminated block of `KEY=value` lines rather
+/// than a single line, so it isn't (yet) given its own message/stream extraction in
+/// `util::matcher` or record assembly in `util::writer` -- both of those still operate one
+/// physical line at a time, the same assumption `CriLineReassembler` exists to work around for
+/// CRI's split P/F lines. A follow-up would need an analogous block-reassembling reader before
+/// journald-export gets full support there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// `{"log":"...","stream":"stdout","time":"..."}` -- one JSON object per line.
+    Docker,
+    /// `<rfc3339> <stdout|stderr> <F|P> <message>` -- the CRI runtime's plain-text format.
+    CRI,
+    /// `journalctl -o export` framing: a blank-line-terminated block of `KEY=value` lines per
+    /// record, with `_SOURCE_REALTIME_TIMESTAMP` (falling back to `__REALTIME_TIMESTAMP`)
+    /// carrying microseconds-since-epoch and `MESSAGE` carrying the text.
+    JournaldExport,
+    /// klog/glog-style `Immdd hh:mm:ss.uuuuuu threadid file:line] message`, where the leading
+    /// character is one of `I`/`W`/`E`/`F` for Info/Warn/Error/Fatal and the year is implied to be
+    /// the current one (klog's header carries no year).
+    Klog,
+    /// One arbitrary JSON object per line with at least a `timestamp` (or `time`) field and a
+    /// `message` (or `msg`) field, but none of Docker's other framing.
+    JsonLines,
+    /// Classic BSD/RFC 3164 syslog: `Mon DD HH:MM:SS host tag: message`. Like `Klog`, the header
+    /// carries no year, so [`Self::parse_timestamp`]'s `year_hint` anchors it.
+    Syslog,
+}
+
+/// Maps `LogRecordsStreamRequest.forced_format` onto the format this agent actually dispatches
+/// on. `LogFormat::Unspecified` (the wire default) maps to `None`, leaving the caller to fall
+/// back to [`FileFormat::detect_from_filename`]/[`FileFormat::sniff`].
+impl From<LogFormat> for Option<FileFormat> {
+    fn from(value: LogFormat) -> Self {
+        match value {
+            LogFormat::Unspecified => None,
+            LogFormat::Docker => Some(FileFormat::Docker),
+            LogFormat::Cri => Some(FileFormat::CRI),
+            LogFormat::JournaldExport => Some(FileFormat::JournaldExport),
+            LogFormat::Klog => Some(FileFormat::Klog),
+            LogFormat::JsonLines => Some(FileFormat::JsonLines),
+            // The wire enum predates `Syslog`; callers that need it force it via content
+            // sniffing instead, same as any other format with no `LogFormat` entry yet.
+        }
+    }
+}
+
+impl FileFormat {
+    /// Guesses the format from the file's name, using the same `-json.log` convention the
+    /// kubelet uses to distinguish its two native formats. Returns `None` when the filename gives
+    /// no hint, so the caller can fall back to [`FileFormat::sniff`].
+    pub fn detect_from_filename(path: &Path) -> Option<FileFormat> {
+        let name = path.to_string_lossy();
+        if name.ends_with("-json.log") {
+            Some(FileFormat::Docker)
+        } else if name.ends_with(".log") {
+            Some(FileFormat::CRI)
+        } else {
+            None
+        }
+    }
+
+    /// Content-sniffing fallback for when the filename doesn't hint at a format: probes each
+    /// candidate format's timestamp parser against a representative line (e.g. the file's first
+    /// line) and returns the first one that can make sense of it, defaulting to `CRI` -- the
+    /// kubelet's plain-text format -- if none do. `year_hint` is forwarded to candidates (like
+    /// `Syslog`/`Klog`) whose timestamp doesn't carry a year; see [`Self::parse_timestamp`].
+    pub fn sniff(sample_line: &str, year_hint: Option<i32>) -> FileFormat {
+        const CANDIDATES: [FileFormat; 6] = [
+            FileFormat::Docker,
+            FileFormat::JournaldExport,
+            FileFormat::Klog,
+            FileFormat::JsonLines,
+            FileFormat::Syslog,
+            FileFormat::CRI,
+        ];
+
+        CANDIDATES
+            .into_iter()
+            // Lenient: a sniff probe should recognize CRI even when the sample line happens to
+            // use the space-separated timestamp `parse_timestamp`'s `strict` flag otherwise
+            // rejects -- it only picks a variant, it doesn't commit to how later lines get parsed.
+            .find(|format| format.parse_timestamp(sample_line, year_hint, false).is_ok())
+            .unwrap_or(FileFormat::CRI)
+    }
+
+    /// Resolves the format to use for `path`: `forced_format` wins if the caller asked for one
+    /// (e.g. via `LogRecordsStreamRequest.forced_format`), otherwise the filename is checked, and
+    /// failing that `file`'s first line is sniffed. `file`'s cursor is restored to the start
+    /// afterwards regardless of which path was taken, so the caller can go on to read it as if
+    /// this call never happened. `year_hint` is forwarded to [`Self::sniff`].
+    pub fn resolve(
+        path: &Path,
+        file: &File,
+        forced_format: Option<FileFormat>,
+        year_hint: Option<i32>,
+    ) -> io::Result<FileFormat> {
+        if let Some(format) = forced_format {
+            return Ok(format);
+        }
+
+        if let Some(format) = FileFormat::detect_from_filename(path) {
+            return Ok(format);
+        }
+
+        let mut first_line = String::new();
+        BufReader::new(file).read_line(&mut first_line)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        Ok(FileFormat::sniff(first_line.trim_end(), year_hint))
+    }
+
+    /// Parses the timestamp carried by a line/record of this format, for
+    /// [`crate::util::offset`]'s nearest-offset binary search. `year_hint` anchors formats whose
+    /// header carries no year (`Klog`, `Syslog`); it's ignored by formats that carry their own
+    /// full date. When absent, those formats fall back to the current UTC year -- callers with a
+    /// better anchor (typically the file's last-modified year, via
+    /// [`year_hint_from_mtime`]) should supply one instead. `strict` only affects `CRI`: `false`
+    /// also accepts a space instead of `T` between date and time, which isn't valid RFC3339 but
+    /// shows up in some aggregated/forwarded logs; pure-kubelet CRI output should keep `strict`
+    /// `true` for the faster, simpler path.
+    pub fn parse_timestamp(
+        &self,
+        line: &str,
+        year_hint: Option<i32>,
+        strict: bool,
+    ) -> Result<DateTime<Utc>, Box<dyn Error>> {
+        match self {
+            FileFormat::Docker | FileFormat::JsonLines => parse_timestamp_json(line),
+            FileFormat::CRI => parse_timestamp_cri(line, strict),
+            FileFormat::JournaldExport => parse_timestamp_journald(line),
+            FileFormat::Klog => parse_timestamp_klog(line, year_hint),
+            FileFormat::Syslog => parse_timestamp_syslog(line, year_hint),
+        }
+    }
+}
+
+/// Derives a year to anchor a year-less timestamp format to from `file`'s own last-modified time.
+/// The natural fallback when a caller has no better anchor (e.g. the pod's observed start time) to
+/// pass as `parse_timestamp`'s `year_hint` -- a log file's contents are rarely older than the year
+/// it was last written.
+pub fn year_hint_from_mtime(file: &File) -> Option<i32> {
+    let modified = file.metadata().ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified).year())
+}
+
+/// Shared by `Docker` (`"timestamp"`/`"time"`) and `JsonLines` (`"timestamp"`/`"time"`).
+fn parse_timestamp_json(line: &str) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let json: serde_json::Value = serde_json::from_str(line)?;
+
+    let timestamp = json
+        .get("timestamp")
+        .or_else(|| json.get("time"))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| format!("missing timestamp field in JSON log: {line}"))?;
+
+    Ok(DateTime::parse_from_rfc3339(timestamp)?.with_timezone(&Utc))
+}
+
+fn parse_timestamp_cri(line: &str, strict: bool) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let (first, rest) = line
+        .split_once(' ')
+        .ok_or_else(|| format!("invalid log line: {line}"))?;
+    let _ = rest;
+
+    if let Ok(ts) = DateTime::parse_from_rfc3339(first) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    if !strict {
+        if let Some(ts) = parse_timestamp_cri_lenient(line) {
+            return Ok(ts.with_timezone(&Utc));
+        }
+    }
+
+    Err(format!("invalid CRI timestamp: {first}").into())
+}
+
+/// Tolerates a space instead of `T` between date and time (e.g.
+/// `2024-10-01 05:40:46.960135302+02:00`), which some aggregated/forwarded CRI-style logs use but
+/// which isn't valid RFC3339. The first two space-separated tokens are rejoined with `T` and
+/// retried as RFC3339, which also normalizes any fixed numeric offset (not just `Z`) via the
+/// caller's `with_timezone(&Utc)`.
+fn parse_timestamp_cri_lenient(line: &str) -> Option<DateTime<chrono::FixedOffset>> {
+    let mut parts = line.splitn(3, ' ');
+    let date = parts.next()?;
+    let time = parts.next()?;
+    DateTime::parse_from_rfc3339(&format!("{date}T{time}")).ok()
+}
+
+/// `_SOURCE_REALTIME_TIMESTAMP=<microseconds since epoch>`, falling back to the field journald
+/// itself stamps every entry with (`__REALTIME_TIMESTAMP`) when the application didn't supply its
+/// own.
+fn parse_timestamp_journald(line: &str) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let micros: i64 = line
+        .lines()
+        .find_map(|field| {
+            field
+                .strip_prefix("_SOURCE_REALTIME_TIMESTAMP=")
+                .or_else(|| field.strip_prefix("__REALTIME_TIMESTAMP="))
+        })
+        .ok_or_else(|| format!("missing realtime timestamp field in journald-export log: {line}"))?
+        .parse()?;
+
+    DateTime::from_timestamp_micros(micros)
+        .ok_or_else(|| format!("realtime timestamp out of range: {micros}").into())
+}
+
+/// klog carries no year in its header, so the parsed timestamp is anchored to `year_hint`
+/// (falling back to the current UTC year when absent) -- good enough for seeking within a single
+/// log file, which never spans a year boundary in practice for the short-lived node components
+/// that write this format.
+fn parse_timestamp_klog(line: &str, year_hint: Option<i32>) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let mut chars = line.chars();
+    let severity = chars
+        .next()
+        .ok_or_else(|| format!("empty klog line: {line}"))?;
+    if !matches!(severity, 'I' | 'W' | 'E' | 'F') {
+        return Err(format!("not a klog severity marker: {severity}").into());
+    }
+
+    let rest = chars.as_str();
+    let header = rest
+        .get(0..20)
+        .ok_or_else(|| format!("klog header too short: {line}"))?;
+
+    let year = year_hint.unwrap_or_else(|| Utc::now().year());
+    let naive = NaiveDateTime::parse_from_str(
+        &format!("{year}{header}"),
+        "%Y%m%d %H:%M:%S%.6f",
+    )?;
+    Ok(naive.and_utc())
+}
+
+/// Classic BSD/RFC 3164 syslog: `Mon DD HH:MM:SS ...`, e.g. `Jul 31 12:00:00 host tag: message`.
+/// Like klog, the header carries no year, so the parsed timestamp is anchored to `year_hint`
+/// (falling back to the current UTC year when absent).
+fn parse_timestamp_syslog(line: &str, year_hint: Option<i32>) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let header = line
+        .get(0..15)
+        .ok_or_else(|| format!("syslog header too short: {line}"))?;
+
+    let year = year_hint.unwrap_or_else(|| Utc::now().year());
+    let naive = NaiveDateTime::parse_from_str(&format!("{year} {header}"), "%Y %b %e %H:%M:%S")?;
+    Ok(naive.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use chrono::Timelike;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_forced_format_over_filename_and_content() {
+        let mut tmpfile = NamedTempFile::new().unwrap();
+        tmpfile.write_all(b"I0731 12:00:00.000000 1 main.go:10] hi\n").unwrap();
+        let file = tmpfile.reopen().unwrap();
+
+        let format = FileFormat::resolve(
+            Path::new("ignored-json.log"),
+            &file,
+            Some(FileFormat::CRI),
+            None,
+        )
+        .unwrap();
+        assert_eq!(format, FileFormat::CRI);
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_content_sniff() {
+        let mut tmpfile = NamedTempFile::new().unwrap();
+        tmpfile.write_all(b"I0731 12:00:00.000000 1 main.go:10] hi\n").unwrap();
+        let file = tmpfile.reopen().unwrap();
+
+        // No `forced_format`, and the temp file's name gives `detect_from_filename` no hint.
+        let format = FileFormat::resolve(tmpfile.path(), &file, None, None).unwrap();
+        assert_eq!(format, FileFormat::Klog);
+
+        // The cursor must be left at the start for the caller's own reads.
+        let mut rest = String::new();
+        BufReader::new(&file).read_line(&mut rest).unwrap();
+        assert_eq!(rest, "I0731 12:00:00.000000 1 main.go:10] hi\n");
+    }
+
+    #[test]
+    fn test_detect_from_filename() {
+        assert_eq!(
+            FileFormat::detect_from_filename(Path::new("/var/log/pods/foo/0.log")),
+            Some(FileFormat::CRI)
+        );
+        assert_eq!(
+            FileFormat::detect_from_filename(Path::new("/var/lib/docker/containers/foo-json.log")),
+            Some(FileFormat::Docker)
+        );
+        assert_eq!(
+            FileFormat::detect_from_filename(Path::new("/var/log/journal/foo.export")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(
+            FileFormat::sniff(
+                r#"{"log":"hi\n","stream":"stdout","time":"2025-07-31T12:00:00Z"}"#,
+                None
+            ),
+            FileFormat::Docker
+        );
+        assert_eq!(
+            FileFormat::sniff("2025-07-31T12:00:00.000000000Z stdout F hello", None),
+            FileFormat::CRI
+        );
+        assert_eq!(
+            FileFormat::sniff("I0731 12:00:00.000000       1 main.go:10] hello", None),
+            FileFormat::Klog
+        );
+        assert_eq!(
+            FileFormat::sniff(r#"{"timestamp":"2025-07-31T12:00:00Z","message":"hello"}"#, None),
+            FileFormat::Docker // Same JSON shape as Docker; Docker is probed first.
+        );
+        assert_eq!(
+            FileFormat::sniff("not a log line at all", None),
+            FileFormat::CRI
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp_journald() {
+        let line = "__REALTIME_TIMESTAMP=1753963200000000\nMESSAGE=hello\n";
+        let ts = FileFormat::JournaldExport.parse_timestamp(line, None, true).unwrap();
+        assert_eq!(ts.timestamp(), 1_753_963_200);
+    }
+
+    #[test]
+    fn test_parse_timestamp_klog() {
+        let year = Utc::now().year();
+        let line = "I0731 12:00:00.123456       1 main.go:10] hello";
+        let ts = FileFormat::Klog.parse_timestamp(line, None, true).unwrap();
+        assert_eq!(ts.year(), year);
+        assert_eq!(ts.month(), 7);
+        assert_eq!(ts.day(), 31);
+    }
+
+    #[test]
+    fn test_parse_timestamp_klog_uses_year_hint() {
+        let line = "I0731 12:00:00.123456       1 main.go:10] hello";
+        let ts = FileFormat::Klog.parse_timestamp(line, Some(2019), true).unwrap();
+        assert_eq!(ts.year(), 2019);
+    }
+
+    #[test]
+    fn test_parse_timestamp_syslog() {
+        let line = "Jul 31 12:00:00 host tag: hello";
+        let ts = FileFormat::Syslog.parse_timestamp(line, Some(2019), true).unwrap();
+        assert_eq!(ts.year(), 2019);
+        assert_eq!(ts.month(), 7);
+        assert_eq!(ts.day(), 31);
+        assert_eq!(ts.hour(), 12);
+    }
+
+    #[test]
+    fn test_sniff_detects_syslog() {
+        assert_eq!(
+            FileFormat::sniff("Jul 31 12:00:00 host tag: hello", Some(2019)),
+            FileFormat::Syslog
+        );
+    }
+
+    #[test]
+    fn test_year_hint_from_mtime() {
+        let tmpfile = NamedTempFile::new().unwrap();
+        let file = tmpfile.reopen().unwrap();
+        assert_eq!(year_hint_from_mtime(&file), Some(Utc::now().year()));
+    }
+
+    #[test]
+    fn test_parse_timestamp_cri_strict_rejects_space_separator() {
+        let line = "2024-10-01 05:40:46.960135302Z stdout F hello";
+        assert!(FileFormat::CRI.parse_timestamp(line, None, true).is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp_cri_lenient_accepts_space_separator() {
+        let line = "2024-10-01 05:40:46.960135302Z stdout F hello";
+        let ts = FileFormat::CRI.parse_timestamp(line, None, false).unwrap();
+        assert_eq!(ts.timestamp(), 1_727_761_246);
+    }
+
+    #[test]
+    fn test_parse_timestamp_cri_lenient_normalizes_fixed_offset_to_utc() {
+        let line = "2024-10-01 07:40:46.960135302+02:00 stdout F hello";
+        let ts = FileFormat::CRI.parse_timestamp(line, None, false).unwrap();
+        assert_eq!(ts.timestamp(), 1_727_761_246);
+    }
+
+    #[test]
+    fn test_parse_timestamp_cri_strict_still_accepts_rfc3339() {
+        let line = "2024-10-01T05:40:46.960135302Z stdout F hello";
+        assert!(FileFormat::CRI.parse_timestamp(line, None, true).is_ok());
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2024-2025 Andres Morey
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+
+use crate::util::format::FileFormat;
+use crate::util::offset::Offset;
+
+/// One line's location and timestamp inside a gzip file's decompressed content, as recorded by
+/// [`build_gz_index`].
+#[derive(Debug, Clone, PartialEq)]
+struct GzLineEntry {
+    timestamp: DateTime<Utc>,
+    /// Offsets here are positions in the *uncompressed* stream, not the gzip file itself.
+    offset: Offset,
+}
+
+/// An index over a gzip-compressed log's lines, built once by [`build_gz_index`] so repeated
+/// [`find_nearest_offset_since_gz`]/[`find_nearest_offset_until_gz`] lookups don't each
+/// re-decompress the file.
+///
+/// zlib's `zran.c` example (the usual reference for this kind of "access point" index) instead
+/// stores sparse checkpoints -- a compressed bit offset plus a 32KB dictionary window every few
+/// hundred KB of uncompressed output -- and reseeks mid-deflate-block on lookup. That relies on
+/// `inflatePrime`/`Z_BLOCK`-style primitives to resume a decoder at an arbitrary bit position,
+/// which `flate2`'s safe API doesn't expose: without them, a fresh decoder can only resume at a
+/// gzip member boundary, and a rotated log's `.gz` file is a single member, so that approach
+/// degenerates to one checkpoint for the whole file -- no better than decompressing from the
+/// start every time.
+///
+/// Since the one decompression pass [`build_gz_index`] already has to make visits every line
+/// anyway, this keeps the full per-line offset/timestamp list instead of sparse checkpoints and
+/// binary-searches it directly in memory. Lookups become free of repeat decompression without
+/// needing any bit-priming support; the trade is O(line count) memory instead of O(checkpoint
+/// count), and a caller that wants the line's *text* (not just its offset) still has to
+/// decompress up to that offset, same as before this index existed.
+#[derive(Debug, Clone, Default)]
+pub struct GzIndex {
+    lines: Vec<GzLineEntry>,
+}
+
+/// Decompresses `file` once, recording every line's uncompressed [`Offset`] and parsed timestamp
+/// into a [`GzIndex`]. Lines whose timestamp doesn't parse are skipped, the same tolerance
+/// [`crate::util::offset::scan_timestamp`] applies when seeking an uncompressed file. `strict` is
+/// forwarded to [`FileFormat::parse_timestamp`] as-is; see there for what it relaxes.
+pub fn build_gz_index(
+    file: &File,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+) -> Result<GzIndex, Box<dyn Error>> {
+    let mut reader = BufReader::new(GzDecoder::new(file));
+    let mut lines = Vec::new();
+    let mut byte_offset = 0u64;
+
+    loop {
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        let trimmed = line.trim_end();
+
+        if let Ok(timestamp) = format.parse_timestamp(trimmed, year_hint, strict) {
+            lines.push(GzLineEntry {
+                timestamp,
+                offset: Offset {
+                    byte_offset,
+                    line_length: trimmed.len() as u64,
+                },
+            });
+        }
+
+        byte_offset += bytes_read as u64;
+    }
+
+    Ok(GzIndex { lines })
+}
+
+/// Binary-searches `index` for the nearest line at or after `target_time`, mirroring
+/// [`crate::util::offset::find_nearest_offset_since`] but over a pre-built [`GzIndex`] instead of
+/// a randomly-seekable `File`.
+pub fn find_nearest_offset_since_gz(index: &GzIndex, target_time: DateTime<Utc>) -> Option<Offset> {
+    let pos = index.lines.partition_point(|entry| entry.timestamp < target_time);
+    index.lines.get(pos).map(|entry| entry.offset)
+}
+
+/// Binary-searches `index` for the nearest line at or before `target_time`, mirroring
+/// [`crate::util::offset::find_nearest_offset_until`] but over a pre-built [`GzIndex`] instead of
+/// a randomly-seekable `File`.
+pub fn find_nearest_offset_until_gz(index: &GzIndex, target_time: DateTime<Utc>) -> Option<Offset> {
+    let pos = index.lines.partition_point(|entry| entry.timestamp <= target_time);
+    if pos == 0 {
+        return None;
+    }
+    index.lines.get(pos - 1).map(|entry| entry.offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    const LINES: [&str; 3] = [
+        "2024-10-01T05:40:00.000000000Z stdout F linenum 1",
+        "2024-10-01T05:40:10.000000000Z stdout F linenum 2",
+        "2024-10-01T05:40:20.000000000Z stdout F linenum 3",
+    ];
+
+    fn create_temp_gz_log(lines: &[&str]) -> Result<NamedTempFile, Box<dyn Error>> {
+        let mut tmpfile = NamedTempFile::new()?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        for &line in lines {
+            encoder.write_all(line.as_bytes())?;
+            encoder.write_all(b"\n")?;
+        }
+        tmpfile.write_all(&encoder.finish()?)?;
+        tmpfile.flush()?;
+        Ok(tmpfile)
+    }
+
+    #[test]
+    fn test_build_gz_index_records_every_line() -> Result<(), Box<dyn Error>> {
+        let tmpfile = create_temp_gz_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let index = build_gz_index(&file, FileFormat::CRI, None, true)?;
+        assert_eq!(index.lines.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_nearest_offset_since_gz() -> Result<(), Box<dyn Error>> {
+        let tmpfile = create_temp_gz_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let index = build_gz_index(&file, FileFormat::CRI, None, true)?;
+
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:05.000000000Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let offset = find_nearest_offset_since_gz(&index, target_time).unwrap();
+        assert_eq!(offset, index.lines[1].offset);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_nearest_offset_until_gz() -> Result<(), Box<dyn Error>> {
+        let tmpfile = create_temp_gz_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let index = build_gz_index(&file, FileFormat::CRI, None, true)?;
+
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:15.000000000Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let offset = find_nearest_offset_until_gz(&index, target_time).unwrap();
+        assert_eq!(offset, index.lines[1].offset);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_nearest_offset_since_gz_past_end_returns_none() -> Result<(), Box<dyn Error>> {
+        let tmpfile = create_temp_gz_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let index = build_gz_index(&file, FileFormat::CRI, None, true)?;
+
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:41:00.000000000Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(find_nearest_offset_since_gz(&index, target_time), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_nearest_offset_until_gz_before_start_returns_none() -> Result<(), Box<dyn Error>> {
+        let tmpfile = create_temp_gz_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let index = build_gz_index(&file, FileFormat::CRI, None, true)?;
+
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:39:00.000000000Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(find_nearest_offset_until_gz(&index, target_time), None);
+
+        Ok(())
+    }
+}
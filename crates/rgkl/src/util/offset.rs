@@ -13,41 +13,79 @@
 // limitations under the License.
 
 use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
     error::Error,
     fs::File,
-    io::{BufRead, BufReader, Seek, SeekFrom},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    time::Duration,
 };
 
 use chrono::{DateTime, Utc};
-use serde_json;
+use regex::Regex;
+
+use crate::util::format::FileFormat;
 
 /// Represents an offset result from find_nearest_offset()
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Offset {
     pub byte_offset: u64,
     pub line_length: u64,
 }
 
 /// Finds the nearest offset to a given timestamp between `min_offset` and
-/// `max_offset` greater than or equal to `target_time`.
-pub fn find_nearest_offset_since(
-    file: &File,
+/// `max_offset` greater than or equal to `target_time`, by bisecting `source`'s byte range: each
+/// step seeks to the midpoint, realigns forward to the next line boundary (never comparing a
+/// mid-line fragment), and compares that line's parsed timestamp against `target_time` to halve
+/// the window -- the `seek_to_time` capability for any seekable reader, not just a `File`, since
+/// `R: Read + Seek` is all the bisection needs. `year_hint` anchors year-less formats (klog,
+/// classic syslog); see [`FileFormat::parse_timestamp`]. `strict` is forwarded to
+/// `parse_timestamp` as well -- `false` tolerates a few non-RFC3339 CRI timestamp shapes, at the
+/// cost of a slower parse path; pure kubelet CRI output should pass `true`.
+pub fn find_nearest_offset_since<R: Read + Seek>(
+    source: R,
     target_time: DateTime<Utc>,
     min_offset: u64,
     max_offset: u64,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
 ) -> Result<Option<Offset>, Box<dyn Error>> {
-    find_nearest_offset(file, target_time, min_offset, max_offset, FindMode::Since)
+    find_nearest_offset(
+        source,
+        target_time,
+        min_offset,
+        max_offset,
+        format,
+        year_hint,
+        strict,
+        FindMode::Since,
+    )
 }
 
 /// Finds the nearest offset to a given timestamp between `min_offset` and
-/// `max_offset` less than or equal to `target_time`.
-pub fn find_nearest_offset_until(
-    file: &File,
+/// `max_offset` less than or equal to `target_time`. `year_hint` anchors year-less formats
+/// (klog, classic syslog); see [`FileFormat::parse_timestamp`]. `strict` is forwarded to
+/// `parse_timestamp` as well; see [`find_nearest_offset_since`].
+pub fn find_nearest_offset_until<R: Read + Seek>(
+    source: R,
     target_time: DateTime<Utc>,
     min_offset: u64,
     max_offset: u64,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
 ) -> Result<Option<Offset>, Box<dyn Error>> {
-    find_nearest_offset(file, target_time, min_offset, max_offset, FindMode::Until)
+    find_nearest_offset(
+        source,
+        target_time,
+        min_offset,
+        max_offset,
+        format,
+        year_hint,
+        strict,
+        FindMode::Until,
+    )
 }
 
 enum FindMode {
@@ -55,11 +93,15 @@ enum FindMode {
     Until,
 }
 
-fn find_nearest_offset(
-    file: &File,
+#[allow(clippy::too_many_arguments)]
+fn find_nearest_offset<R: Read + Seek>(
+    source: R,
     target_time: DateTime<Utc>,
     min_offset: u64,
     max_offset: u64,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
     mode: FindMode,
 ) -> Result<Option<Offset>, Box<dyn Error>> {
     if max_offset == 0 {
@@ -69,11 +111,9 @@ fn find_nearest_offset(
     let mut left: i64 = min_offset as i64;
     let mut right: i64 = (max_offset - 1) as i64;
 
-    //    let mut result: Option<u64> = None;
-
     let mut result: Option<Offset> = None;
 
-    let mut reader = BufReader::new(file);
+    let mut reader = BufReader::new(source);
 
     while left <= right {
         let mid = (left + right) / 2;
@@ -82,7 +122,7 @@ fn find_nearest_offset(
         reader.seek(SeekFrom::Start(mid as u64))?;
 
         // Scan for the next valid timestamp.
-        let (new_mid, res_opt) = scan_timestamp(&mut reader, right, mid)?;
+        let (new_mid, res_opt) = scan_timestamp(&mut reader, right, mid, format, year_hint, strict)?;
 
         match res_opt {
             Some((ts, line_length)) => {
@@ -128,10 +168,13 @@ type ScanResultTuple = (DateTime<Utc>, usize);
 /// Reads from the given buffered reader starting at `start_pos` up to `right`
 /// to find a line with a valid timestamp. Returns the position where the
 /// timestamp was found along with the parsed timestamp (if any).
-fn scan_timestamp(
-    reader: &mut BufReader<&File>,
+fn scan_timestamp<R: Read>(
+    reader: &mut BufReader<R>,
     right: i64,
     start_pos: i64,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
 ) -> Result<(i64, Option<ScanResultTuple>), Box<dyn Error>> {
     let mut pos = start_pos;
     while pos <= right {
@@ -144,7 +187,7 @@ fn scan_timestamp(
             return Ok((start_pos, None));
         }
 
-        if let Ok(ts) = parse_timestamp(&line) {
+        if let Ok(ts) = format.parse_timestamp(&line, year_hint, strict) {
             return Ok((pos, Some((ts, line.len()))));
         }
 
@@ -154,31 +197,374 @@ fn scan_timestamp(
     Ok((start_pos, None))
 }
 
-/// Attempts to parse a timestamp from the beginning of the log line.
-/// The log line is expected to start with an RFC 3339 formatted timestamp
-/// or be in Docker JSON format with a "timestamp" field.
-fn parse_timestamp(line: &str) -> Result<DateTime<Utc>, Box<dyn std::error::Error>> {
-    // Check if the line starts with '{' which indicates JSON format (Docker logs)
-    if line.starts_with('{') {
-        // Parse the JSON
-        let json: serde_json::Value = serde_json::from_str(line)?;
-        
-        // Extract the timestamp field
-        if let Some(timestamp) = json.get("timestamp").and_then(|t| t.as_str()) {
-            let ts = DateTime::parse_from_rfc3339(timestamp)?.with_timezone(&Utc);
-            return Ok(ts);
-        } else {
-            return Err(format!("missing timestamp field in JSON log: {}", line).into());
+/// One file's next pending line in a [`merge_since`]/[`merge_until`] merge, ordered so
+/// [`BinaryHeap`] (a max-heap) wrapped in [`Reverse`] pops the earliest timestamp first, with
+/// ties broken by `file_index` so interleaving identical timestamps is stable across runs.
+struct PendingLine<'a> {
+    timestamp: DateTime<Utc>,
+    file_index: usize,
+    offset: Offset,
+    line: String,
+    reader: BufReader<&'a File>,
+    /// Byte position where the next `read_line` on `reader` will start.
+    cursor: u64,
+}
+
+impl PartialEq for PendingLine<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.timestamp == other.timestamp && self.file_index == other.file_index
+    }
+}
+
+impl Eq for PendingLine<'_> {}
+
+impl PartialOrd for PendingLine<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingLine<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.timestamp
+            .cmp(&other.timestamp)
+            .then(self.file_index.cmp(&other.file_index))
+    }
+}
+
+/// Advances `entry`'s reader to the next line with a parseable timestamp, skipping malformed
+/// lines exactly like [`scan_timestamp`] does, and pushes it back onto `heap` to compete for the
+/// next slot. Leaves the file out of the heap (effectively dropping it from the merge) once it
+/// hits EOF.
+fn requeue_next_line<'a>(
+    mut entry: PendingLine<'a>,
+    heap: &mut BinaryHeap<Reverse<PendingLine<'a>>>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+) {
+    loop {
+        let mut line = String::new();
+        let start = entry.cursor;
+        let bytes_read = entry.reader.read_line(&mut line).unwrap_or(0);
+        if bytes_read == 0 {
+            return;
+        }
+        entry.cursor += bytes_read as u64;
+
+        let trimmed = line.trim_end();
+        if let Ok(timestamp) = format.parse_timestamp(trimmed, year_hint, strict) {
+            entry.timestamp = timestamp;
+            entry.offset = Offset {
+                byte_offset: start,
+                line_length: trimmed.len() as u64,
+            };
+            entry.line = trimmed.to_string();
+            heap.push(Reverse(entry));
+            return;
+        }
+    }
+}
+
+/// Iterator returned by [`merge_since`]/[`merge_until`]: yields `(file_index, Offset, String)`
+/// in global chronological order across every file that was given a starting point.
+pub struct MergedLines<'a> {
+    heap: BinaryHeap<Reverse<PendingLine<'a>>>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+    until: Option<DateTime<Utc>>,
+}
+
+impl Iterator for MergedLines<'_> {
+    type Item = (usize, Offset, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse(entry) = self.heap.pop()?;
+
+        if let Some(until) = self.until {
+            if entry.timestamp > until {
+                // This file (and everything after it, since the heap is chronologically
+                // ordered) has run past the requested end time; nothing more to emit.
+                self.heap.clear();
+                return None;
+            }
+        }
+
+        let item = (entry.file_index, entry.offset, entry.line.clone());
+        requeue_next_line(entry, &mut self.heap, self.format, self.year_hint, self.strict);
+        Some(item)
+    }
+}
+
+/// Streams lines from every file in `files` in global chronological order starting at the first
+/// line (in each file) at or after `target_time`, the way the super_speedy s4 driver merges
+/// multiple log files by timestamp. A file whose [`find_nearest_offset_since`] returns `None`
+/// (nothing in range) is simply never inserted into the merge. Malformed lines encountered while
+/// advancing a file are skipped exactly like [`scan_timestamp`], and ties on identical timestamps
+/// break deterministically by `file_index` so output is stable across runs. `year_hint` anchors
+/// year-less formats (klog, classic syslog); `strict` is forwarded to `parse_timestamp`; see
+/// [`FileFormat::parse_timestamp`].
+pub fn merge_since(
+    files: &[File],
+    target_time: DateTime<Utc>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+) -> Result<MergedLines<'_>, Box<dyn Error>> {
+    let mut heap = BinaryHeap::with_capacity(files.len());
+
+    for (file_index, file) in files.iter().enumerate() {
+        let max_offset = file.metadata()?.len();
+        let Some(offset) =
+            find_nearest_offset_since(file, target_time, 0, max_offset, format, year_hint, strict)?
+        else {
+            continue;
+        };
+
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(offset.byte_offset))?;
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line)?;
+        let trimmed = line.trim_end();
+        let Ok(timestamp) = format.parse_timestamp(trimmed, year_hint, strict) else {
+            // The offset finder guarantees a parseable line here; be defensive anyway rather
+            // than panic on a file that changed out from under us.
+            continue;
+        };
+
+        heap.push(Reverse(PendingLine {
+            timestamp,
+            file_index,
+            offset,
+            line: trimmed.to_string(),
+            cursor: offset.byte_offset + bytes_read as u64,
+            reader,
+        }));
+    }
+
+    Ok(MergedLines {
+        heap,
+        format,
+        year_hint,
+        strict,
+        until: None,
+    })
+}
+
+/// Streams lines from every file in `files` in global chronological order from the start of each
+/// file up through the last line at or before `target_time`. A file whose
+/// [`find_nearest_offset_until`] returns `None` (nothing in range) is never inserted into the
+/// merge. Otherwise the same ordering, malformed-line tolerance, tie-breaking, `year_hint`, and
+/// `strict` rules as [`merge_since`] apply.
+pub fn merge_until(
+    files: &[File],
+    target_time: DateTime<Utc>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+) -> Result<MergedLines<'_>, Box<dyn Error>> {
+    let mut heap = BinaryHeap::with_capacity(files.len());
+
+    for (file_index, file) in files.iter().enumerate() {
+        let max_offset = file.metadata()?.len();
+        if find_nearest_offset_until(file, target_time, 0, max_offset, format, year_hint, strict)?
+            .is_none()
+        {
+            continue;
+        }
+
+        let mut reader = BufReader::new(file);
+        let (cursor, parsed) = scan_timestamp(
+            &mut reader,
+            (max_offset - 1) as i64,
+            0,
+            format,
+            year_hint,
+            strict,
+        )?;
+        let Some((timestamp, line_length)) = parsed else {
+            continue;
+        };
+
+        reader.seek(SeekFrom::Start(cursor as u64))?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let trimmed = line.trim_end().to_string();
+
+        heap.push(Reverse(PendingLine {
+            timestamp,
+            file_index,
+            offset: Offset {
+                byte_offset: cursor as u64,
+                line_length: line_length as u64,
+            },
+            line: trimmed,
+            cursor: cursor as u64 + line.len() as u64,
+            reader,
+        }));
+    }
+
+    Ok(MergedLines {
+        heap,
+        format,
+        year_hint,
+        strict,
+        until: Some(target_time),
+    })
+}
+
+/// Resolves the `[since, until]` byte range via `find_nearest_offset_since`/
+/// `find_nearest_offset_until`, returning a reader positioned at its start plus the byte offset
+/// one past its last line, or `None` when `max_offset == 0` or no line matches `since`. Shared by
+/// [`count_in_range`] and [`histogram_in_range`], which otherwise only differ in what they do
+/// with each line once it's within range. `strict` is forwarded to `parse_timestamp`; see
+/// [`FileFormat::parse_timestamp`].
+fn lines_in_range<'a>(
+    file: &'a File,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+) -> Result<Option<(BufReader<&'a File>, u64)>, Box<dyn Error>> {
+    let max_offset = file.metadata()?.len();
+    if max_offset == 0 {
+        return Ok(None);
+    }
+
+    let Some(start) =
+        find_nearest_offset_since(file, since, 0, max_offset, format, year_hint, strict)?
+    else {
+        return Ok(None);
+    };
+    let Some(end) = find_nearest_offset_until(
+        file,
+        until,
+        start.byte_offset,
+        max_offset,
+        format,
+        year_hint,
+        strict,
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let mut reader = BufReader::new(file);
+    reader.seek(SeekFrom::Start(start.byte_offset))?;
+
+    Ok(Some((reader, end.byte_offset + end.line_length)))
+}
+
+/// Counts log entries whose timestamp falls in `[since, until]`, optionally restricted to lines
+/// whose body also matches `pattern`, mirroring what check_timed_logs_fast does for "how many
+/// entries fall in this window". Cheaper than streaming full records when a caller only wants a
+/// count. Malformed lines are skipped without aborting, exactly like [`scan_timestamp`]'s
+/// tolerance. `strict` is forwarded to `parse_timestamp`; see [`FileFormat::parse_timestamp`].
+pub fn count_in_range(
+    file: &File,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+    pattern: Option<&Regex>,
+) -> Result<u64, Box<dyn Error>> {
+    let Some((mut reader, end_offset)) =
+        lines_in_range(file, since, until, format, year_hint, strict)?
+    else {
+        return Ok(0);
+    };
+
+    let mut count = 0u64;
+    let mut pos = reader.stream_position()?;
+    while pos < end_offset {
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        pos += bytes_read as u64;
+
+        let trimmed = line.trim_end();
+        let Ok(timestamp) = format.parse_timestamp(trimmed, year_hint, strict) else {
+            continue;
+        };
+        if timestamp < since || timestamp > until {
+            continue;
         }
-    } else {
-        // Original CRI format parsing
-        let parts: Vec<&str> = line.splitn(2, ' ').collect();
-        if parts.len() < 2 {
-            return Err(format!("invalid log line: {}", line).into());
+        if pattern.is_some_and(|pattern| !pattern.is_match(trimmed)) {
+            continue;
         }
-        let ts = DateTime::parse_from_rfc3339(parts[0])?.with_timezone(&Utc);
-        Ok(ts)
+
+        count += 1;
     }
+
+    Ok(count)
+}
+
+/// One time bucket's match count, as returned by [`histogram_in_range`]. Mirrors the shape of
+/// `cluster_agent`'s `LogRecordBucket`, the gRPC-facing equivalent computed over parsed
+/// `LogRecord`s by `stream_backward::aggregate_backward` instead of raw lines here.
+#[derive(Debug, PartialEq)]
+pub struct RangeBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub count: u64,
+}
+
+/// Buckets log entries whose timestamp falls in `[since, until]` by `bucket` width, mirroring
+/// [`count_in_range`]'s single-pass approach but returning a histogram instead of a total.
+/// Buckets with zero matches are omitted, sorted ascending by `bucket_start`, the same convention
+/// `LogRecordsAggregateResponse.buckets` uses. Malformed lines are skipped without aborting,
+/// exactly like [`scan_timestamp`]'s tolerance. `strict` is forwarded to `parse_timestamp`; see
+/// [`FileFormat::parse_timestamp`].
+pub fn histogram_in_range(
+    file: &File,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    format: FileFormat,
+    year_hint: Option<i32>,
+    strict: bool,
+    bucket: Duration,
+) -> Result<Vec<RangeBucket>, Box<dyn Error>> {
+    let Some((mut reader, end_offset)) =
+        lines_in_range(file, since, until, format, year_hint, strict)?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let bucket_secs = bucket.as_secs().max(1) as i64;
+    let mut counts: BTreeMap<i64, u64> = BTreeMap::new();
+
+    let mut pos = reader.stream_position()?;
+    while pos < end_offset {
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        pos += bytes_read as u64;
+
+        let trimmed = line.trim_end();
+        let Ok(timestamp) = format.parse_timestamp(trimmed, year_hint, strict) else {
+            continue;
+        };
+        if timestamp < since || timestamp > until {
+            continue;
+        }
+
+        let bucket_start = timestamp.timestamp() - timestamp.timestamp().rem_euclid(bucket_secs);
+        *counts.entry(bucket_start).or_insert(0) += 1;
+    }
+
+    Ok(counts
+        .into_iter()
+        .filter_map(|(secs, count)| {
+            DateTime::from_timestamp(secs, 0)
+                .map(|bucket_start| RangeBucket { bucket_start, count })
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -270,7 +656,15 @@ mod tests_find_nearest_offset_since {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_since(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_since(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
@@ -302,7 +696,15 @@ mod tests_find_nearest_offset_since {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_since(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_since(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
@@ -318,7 +720,15 @@ mod tests_find_nearest_offset_since {
 
         let target_str = "2024-10-01T05:40:23.308676722Z";
         let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-        let offset = find_nearest_offset_since(&file, target_time, 0, max_offset)?;
+        let offset = find_nearest_offset_since(
+            &file,
+            target_time,
+            0,
+            max_offset,
+            FileFormat::CRI,
+            None,
+            true,
+        )?;
         assert_eq!(offset, None, "target: {}", target_str);
 
         Ok(())
@@ -334,7 +744,15 @@ mod tests_find_nearest_offset_since {
 
         let target_str = "2024-10-01T05:40:23.308676722Z";
         let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-        let offset = find_nearest_offset_since(&file, target_time, 0, max_offset)?;
+        let offset = find_nearest_offset_since(
+            &file,
+            target_time,
+            0,
+            max_offset,
+            FileFormat::CRI,
+            None,
+            true,
+        )?;
         assert_eq!(offset, None, "target: {}", target_str);
 
         Ok(())
@@ -384,7 +802,15 @@ mod tests_find_nearest_offset_since {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_since(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_since(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
@@ -423,12 +849,61 @@ mod tests_find_nearest_offset_since {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_since(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_since(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
         Ok(())
     }
+
+    #[test]
+    fn test_works_over_any_seekable_reader_not_just_file() -> Result<(), Box<dyn Error>> {
+        // The bisection only needs `Read + Seek`, so it should find the same offset over an
+        // in-memory `Cursor` as it would scanning a `File` with identical bytes.
+        use std::io::Cursor;
+
+        let lines = [
+            "2024-10-01T05:40:46.960135302Z stdout F linenum 1",
+            "2024-10-01T05:40:48.840712595Z stdout F linenum 2",
+            "2024-10-01T05:40:50.075182095Z stdout F linenum 3",
+        ];
+        let mut data = Vec::new();
+        for line in lines {
+            data.extend_from_slice(line.as_bytes());
+            data.push(b'\n');
+        }
+        let max_offset = data.len() as u64;
+
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:48.840712595Z")?
+            .with_timezone(&Utc);
+        let offset = find_nearest_offset_since(
+            Cursor::new(data),
+            target_time,
+            0,
+            max_offset,
+            FileFormat::CRI,
+            None,
+            true,
+        )?;
+
+        assert_eq!(
+            offset,
+            Some(Offset {
+                byte_offset: lines[0].len() as u64 + 1,
+                line_length: lines[1].len() as u64,
+            })
+        );
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -491,7 +966,15 @@ mod tests_find_nearest_offset_until {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_until(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_until(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
@@ -523,7 +1006,15 @@ mod tests_find_nearest_offset_until {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_until(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_until(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
@@ -539,7 +1030,15 @@ mod tests_find_nearest_offset_until {
 
         let target_str = "2024-10-01T05:40:23.308676722Z";
         let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-        let offset = find_nearest_offset_until(&file, target_time, 0, max_offset)?;
+        let offset = find_nearest_offset_until(
+            &file,
+            target_time,
+            0,
+            max_offset,
+            FileFormat::CRI,
+            None,
+            true,
+        )?;
         assert_eq!(offset, None, "target: {}", target_str);
 
         Ok(())
@@ -555,7 +1054,15 @@ mod tests_find_nearest_offset_until {
 
         let target_str = "2024-10-01T05:40:23.308676722Z";
         let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-        let offset = find_nearest_offset_until(&file, target_time, 0, max_offset)?;
+        let offset = find_nearest_offset_until(
+            &file,
+            target_time,
+            0,
+            max_offset,
+            FileFormat::CRI,
+            None,
+            true,
+        )?;
         assert_eq!(offset, None, "target: {}", target_str);
 
         Ok(())
@@ -605,7 +1112,15 @@ mod tests_find_nearest_offset_until {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_until(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_until(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
@@ -644,10 +1159,308 @@ mod tests_find_nearest_offset_until {
 
         for (target_str, expected) in test_cases {
             let target_time = DateTime::parse_from_rfc3339(target_str)?.with_timezone(&Utc);
-            let offset = find_nearest_offset_until(&file, target_time, 0, max_offset)?;
+            let offset = find_nearest_offset_until(
+                &file,
+                target_time,
+                0,
+                max_offset,
+                FileFormat::CRI,
+                None,
+                true,
+            )?;
             assert_eq!(offset.as_ref(), expected, "target: {}", target_str);
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests_merge {
+    use super::*;
+
+    #[test]
+    fn test_merge_since_interleaves_by_timestamp() -> Result<(), Box<dyn Error>> {
+        let (file_a, _) = common::create_temp_log(&[
+            "2024-10-01T05:40:00.000000000Z stdout F a1",
+            "2024-10-01T05:40:02.000000000Z stdout F a2",
+            "2024-10-01T05:40:04.000000000Z stdout F a3",
+        ])?;
+        let (file_b, _) = common::create_temp_log(&[
+            "2024-10-01T05:40:01.000000000Z stdout F b1",
+            "2024-10-01T05:40:03.000000000Z stdout F b2",
+        ])?;
+
+        let files = [file_a.into_file(), file_b.into_file()];
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:00.000000000Z")?
+            .with_timezone(&Utc);
+
+        let merged: Vec<_> = merge_since(&files, target_time, FileFormat::CRI, None, true)?
+            .map(|(file_index, _offset, line)| (file_index, line))
+            .collect();
+
+        assert_eq!(
+            merged,
+            vec![
+                (0, "2024-10-01T05:40:00.000000000Z stdout F a1".to_owned()),
+                (1, "2024-10-01T05:40:01.000000000Z stdout F b1".to_owned()),
+                (0, "2024-10-01T05:40:02.000000000Z stdout F a2".to_owned()),
+                (1, "2024-10-01T05:40:03.000000000Z stdout F b2".to_owned()),
+                (0, "2024-10-01T05:40:04.000000000Z stdout F a3".to_owned()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_since_skips_files_with_no_match() -> Result<(), Box<dyn Error>> {
+        let (file_a, _) = common::create_temp_log(&[
+            "2024-10-01T05:40:00.000000000Z stdout F a1",
+        ])?;
+        let (file_b, _) = common::create_temp_log(&[
+            "2024-10-01T05:39:00.000000000Z stdout F too-early",
+        ])?;
+
+        let files = [file_a.into_file(), file_b.into_file()];
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:00.000000000Z")?
+            .with_timezone(&Utc);
+
+        let merged: Vec<_> = merge_since(&files, target_time, FileFormat::CRI, None, true)?
+            .map(|(file_index, _offset, line)| (file_index, line))
+            .collect();
+
+        assert_eq!(
+            merged,
+            vec![(0, "2024-10-01T05:40:00.000000000Z stdout F a1".to_owned())]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_since_breaks_ties_by_file_index() -> Result<(), Box<dyn Error>> {
+        let (file_a, _) = common::create_temp_log(&["2024-10-01T05:40:00.000000000Z stdout F a1"])?;
+        let (file_b, _) = common::create_temp_log(&["2024-10-01T05:40:00.000000000Z stdout F b1"])?;
+
+        let files = [file_a.into_file(), file_b.into_file()];
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:00.000000000Z")?
+            .with_timezone(&Utc);
+
+        let merged: Vec<_> = merge_since(&files, target_time, FileFormat::CRI, None, true)?
+            .map(|(file_index, _offset, _line)| file_index)
+            .collect();
+
+        assert_eq!(merged, vec![0, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_until_stops_at_target_time() -> Result<(), Box<dyn Error>> {
+        let (file_a, _) = common::create_temp_log(&[
+            "2024-10-01T05:40:00.000000000Z stdout F a1",
+            "2024-10-01T05:40:02.000000000Z stdout F a2",
+            "2024-10-01T05:40:04.000000000Z stdout F a3",
+        ])?;
+
+        let files = [file_a.into_file()];
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:02.000000000Z")?
+            .with_timezone(&Utc);
+
+        let merged: Vec<_> = merge_until(&files, target_time, FileFormat::CRI, None, true)?
+            .map(|(_file_index, _offset, line)| line)
+            .collect();
+
+        assert_eq!(
+            merged,
+            vec![
+                "2024-10-01T05:40:00.000000000Z stdout F a1".to_owned(),
+                "2024-10-01T05:40:02.000000000Z stdout F a2".to_owned(),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_until_skips_files_entirely_after_target_time() -> Result<(), Box<dyn Error>> {
+        let (file_a, _) = common::create_temp_log(&[
+            "2024-10-01T05:41:00.000000000Z stdout F too-late",
+        ])?;
+
+        let files = [file_a.into_file()];
+        let target_time = DateTime::parse_from_rfc3339("2024-10-01T05:40:00.000000000Z")?
+            .with_timezone(&Utc);
+
+        let merged: Vec<_> = merge_until(&files, target_time, FileFormat::CRI, None, true)?.collect();
+
+        assert_eq!(merged, vec![]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_range {
+    use super::*;
+
+    const LINES: [&str; 5] = [
+        "2024-10-01T05:40:00.000000000Z stdout F one",
+        "2024-10-01T05:40:10.000000000Z stdout F two",
+        "2024-10-01T05:40:20.000000000Z stdout F three",
+        "2024-10-01T05:40:30.000000000Z stdout F four",
+        "2024-10-01T05:40:40.000000000Z stdout F five",
+    ];
+
+    fn range(start_str: &str, stop_str: &str) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            DateTime::parse_from_rfc3339(start_str).unwrap().with_timezone(&Utc),
+            DateTime::parse_from_rfc3339(stop_str).unwrap().with_timezone(&Utc),
+        )
+    }
+
+    #[test]
+    fn test_count_in_range() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:40:10.000000000Z",
+            "2024-10-01T05:40:30.000000000Z",
+        );
+
+        let count = count_in_range(&file, since, until, FileFormat::CRI, None, true, None)?;
+        assert_eq!(count, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_in_range_with_pattern() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:40:00.000000000Z",
+            "2024-10-01T05:40:40.000000000Z",
+        );
+        let pattern = Regex::new("two|four")?;
+
+        let count = count_in_range(&file, since, until, FileFormat::CRI, None, true, Some(&pattern))?;
+        assert_eq!(count, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_in_range_empty_file_returns_zero() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&[])?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:40:00.000000000Z",
+            "2024-10-01T05:40:40.000000000Z",
+        );
+
+        let count = count_in_range(&file, since, until, FileFormat::CRI, None, true, None)?;
+        assert_eq!(count, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_in_range_no_match_returns_zero() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:41:00.000000000Z",
+            "2024-10-01T05:42:00.000000000Z",
+        );
+
+        let count = count_in_range(&file, since, until, FileFormat::CRI, None, true, None)?;
+        assert_eq!(count, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_in_range_buckets_ascending() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&LINES)?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:40:00.000000000Z",
+            "2024-10-01T05:40:40.000000000Z",
+        );
+
+        let histogram = histogram_in_range(
+            &file,
+            since,
+            until,
+            FileFormat::CRI,
+            None,
+            true,
+            Duration::from_secs(20),
+        )?;
+
+        let counts: Vec<u64> = histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 2, 1]);
+
+        // Ascending bucket_start.
+        for pair in histogram.windows(2) {
+            assert!(pair[0].bucket_start < pair[1].bucket_start);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_in_range_omits_empty_buckets() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&[
+            "2024-10-01T05:40:00.000000000Z stdout F one",
+            "2024-10-01T05:41:00.000000000Z stdout F two",
+        ])?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:40:00.000000000Z",
+            "2024-10-01T05:41:00.000000000Z",
+        );
+
+        let histogram = histogram_in_range(
+            &file,
+            since,
+            until,
+            FileFormat::CRI,
+            None,
+            true,
+            Duration::from_secs(10),
+        )?;
+
+        // 6 buckets span the window, but only the first and last have entries.
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram[0].count, 1);
+        assert_eq!(histogram[1].count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_in_range_empty_file_returns_empty() -> Result<(), Box<dyn Error>> {
+        let (tmpfile, _) = common::create_temp_log(&[])?;
+        let file = tmpfile.into_file();
+        let (since, until) = range(
+            "2024-10-01T05:40:00.000000000Z",
+            "2024-10-01T05:40:40.000000000Z",
+        );
+
+        let histogram = histogram_in_range(
+            &file,
+            since,
+            until,
+            FileFormat::CRI,
+            None,
+            true,
+            Duration::from_secs(10),
+        )?;
+        assert_eq!(histogram, vec![]);
+
+        Ok(())
+    }
+}
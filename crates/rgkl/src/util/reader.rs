@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 
 use crate::util::format::FileFormat;
@@ -21,6 +22,18 @@ use tokio_util::sync::CancellationToken;
 const LOG_TRIMMER_READER_BUFFER_SIZE: usize = 32 * 1024; // 32 KB
 const REVERSE_READER_CHUNK_SIZE: usize = 64 * 1024; // 64KB
 pub const TRUNCATION_SENTINEL: u8 = 0x1F;
+/// Width of the truncated-byte-count hex field in a truncation marker: a `u64` rendered as
+/// uppercase hex, e.g. `{:016X}` — see `append_truncation_marker_raw`/`_json` and
+/// `writer::normalize_message`.
+pub const TRUNCATION_HEX_LEN: usize = 16;
+
+/// Caps how many bytes a single stream's pending `P`-run may accumulate in
+/// [`CriLineReassemblingReader`] or [`CriLineReassemblingReverseReader`] before giving up on
+/// seeing the rest of the run and emitting what's been collected so far. Guards against unbounded
+/// memory growth if a stream's terminating `F` record never arrives (a buggy runtime, or a log cut
+/// off mid-write) -- 8 MiB is generous for any real container log line while still bounding the
+/// worst case.
+const CRI_REASSEMBLY_MAX_PENDING_BYTES: usize = 8 * 1024 * 1024;
 
 #[derive(Debug)]
 pub struct TermReader<R> {
@@ -45,21 +58,33 @@ impl<R: Read> Read for TermReader<R> {
     }
 }
 
+/// Truncates each record this reads down to `truncate_at_bytes`, one physical line at a time --
+/// it has no notion of a CRI `P`/`F` run spanning several lines, so a caller wanting truncation to
+/// apply to the *reassembled* message rather than each raw fragment must wrap a
+/// [`CriLineReassemblingReader`] underneath this one (as `stream_forward` does), not pass it a
+/// flag: by the time a line reaches here it's already a single `F`-tagged logical record, so
+/// truncation (and its one marker) naturally lands on the full reassembled message for free. See
+/// [`CriLineReassemblingReader`]'s doc comment for the reassembly itself.
 #[derive(Debug)]
 pub struct LogTrimmerReader<R> {
     input: BufReader<R>,
     format: FileFormat,
     truncate_at_bytes: usize,
     truncate_enabled: bool,
+    truncate_utf8_safe: bool,
     docker_line_buf: Vec<u8>,
     internal_buf: Vec<u8>,
     pos: usize,
+    cancel_token: Option<CancellationToken>,
 }
 
 impl<R: Read> LogTrimmerReader<R> {
     /// Creates a new LogTrimmerReader.
     /// The `format` is used to detect where the log message starts.
     /// If `truncate_at_bytes` is 0, truncation is disabled (pass-through mode).
+    /// Truncation lands exactly on `truncate_at_bytes` by default, which can split a multibyte
+    /// UTF-8 sequence in half; see [`Self::with_utf8_safe_truncation`] to back the cut point off
+    /// to the nearest character boundary instead.
     pub fn new(reader: R, format: FileFormat, truncate_at_bytes64: u64) -> Self {
         let truncate_at_bytes = truncate_at_bytes64 as usize;
         Self {
@@ -67,12 +92,38 @@ impl<R: Read> LogTrimmerReader<R> {
             format,
             truncate_at_bytes,
             truncate_enabled: truncate_at_bytes > 0,
+            truncate_utf8_safe: false,
             docker_line_buf: Vec::with_capacity(LOG_TRIMMER_READER_BUFFER_SIZE),
             internal_buf: Vec::with_capacity(Self::buffer_capacity(truncate_at_bytes)),
             pos: 0,
+            cancel_token: None,
         }
     }
 
+    /// Opts into backing the truncation cut point off to the nearest UTF-8 character boundary at
+    /// or before `truncate_at_bytes`, so a multibyte sequence straddling the limit is dropped
+    /// whole instead of split into invalid UTF-8. Off by default to keep the existing
+    /// byte-exact behavior for callers relying on it.
+    pub fn with_utf8_safe_truncation(mut self, enabled: bool) -> Self {
+        self.truncate_utf8_safe = enabled;
+        self
+    }
+
+    /// Opts into checking `token` for cancellation at the top of every `fill_buf`/`consume`
+    /// iteration inside `refill_buffer_*`/`discard_rest_of_line`'s inner loops, so a pathologically
+    /// long or newline-free line can't hold this reader hostage past a caller's cancellation --
+    /// unlike [`TermReader`], which only checks between whole `read` calls. Not set by default,
+    /// since most callers already sit behind a `TermReader` and don't need a second check here.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
     #[inline]
     fn buffer_capacity(truncate_at_bytes: usize) -> usize {
         // Reserve enough room for header + message up to the truncate limit, but keep
@@ -81,11 +132,81 @@ impl<R: Read> LogTrimmerReader<R> {
         std::cmp::max(LOG_TRIMMER_READER_BUFFER_SIZE, target)
     }
 
+    /// Backs `take` (a byte count into `buf`) off to the nearest UTF-8 character boundary at or
+    /// before its current value, when [`Self::with_utf8_safe_truncation`] was enabled: a
+    /// continuation byte (`10xxxxxx`) at `buf[take]` means the cut landed inside a multibyte
+    /// sequence, so it's walked back until it doesn't. Assumes `buf` and `take` come from a
+    /// single `fill_buf` chunk, same as the rest of this reader's line parsing -- a sequence
+    /// straddling two chunks isn't backed off differently than today's byte-exact behavior.
+    #[inline]
+    fn adjust_take_to_utf8_boundary(&self, buf: &[u8], mut take: usize) -> usize {
+        if self.truncate_utf8_safe {
+            while take > 0 && buf[take] & 0xC0 == 0x80 {
+                take -= 1;
+            }
+        }
+        take
+    }
+
     fn refill_buffer(&mut self) -> io::Result<bool> {
         match self.format {
             FileFormat::Docker => self.refill_buffer_docker(),
             FileFormat::CRI => self.refill_buffer_cri(),
+            // None of these have a framing byte-prefix this reader can locate a message's start
+            // and end within cheaply (JournaldExport's message spans a whole KEY=value block
+            // rather than living at a fixed offset in one line; Klog's and Syslog's prefixes are
+            // variable-width; JsonLines doesn't guarantee "message" is even the first field).
+            // Until one of those gets its own message-boundary-aware variant, truncation is
+            // applied to the raw line instead of just the message body.
+            FileFormat::JournaldExport
+            | FileFormat::Klog
+            | FileFormat::JsonLines
+            | FileFormat::Syslog => self.refill_buffer_passthrough(),
+        }
+    }
+
+    /// Truncates at the raw line boundary rather than a format-specific message boundary -- see
+    /// `refill_buffer`'s comment on why formats without a fixed-offset message start use this.
+    fn refill_buffer_passthrough(&mut self) -> io::Result<bool> {
+        self.internal_buf.clear();
+        self.pos = 0;
+
+        self.docker_line_buf.clear();
+        loop {
+            if self.is_cancelled() {
+                return Ok(false);
+            }
+
+            let available = self.input.fill_buf()?;
+            if available.is_empty() {
+                if self.docker_line_buf.is_empty() {
+                    return Ok(false);
+                }
+                break;
+            }
+
+            if let Some(rel_idx) = memchr(b'\n', available) {
+                let take = rel_idx + 1;
+                self.docker_line_buf.extend_from_slice(&available[..take]);
+                self.input.consume(take);
+                break;
+            }
+            self.docker_line_buf.extend_from_slice(available);
+            let len = available.len();
+            self.input.consume(len);
+        }
+
+        if !self.truncate_enabled || self.docker_line_buf.len() <= self.truncate_at_bytes {
+            self.internal_buf.extend_from_slice(&self.docker_line_buf);
+            return Ok(true);
         }
+
+        let truncated_bytes = (self.docker_line_buf.len() - self.truncate_at_bytes) as u64;
+        self.internal_buf
+            .extend_from_slice(&self.docker_line_buf[..self.truncate_at_bytes]);
+        Self::append_truncation_marker_raw(&mut self.internal_buf, truncated_bytes);
+
+        Ok(true)
     }
 
     fn refill_buffer_cri(&mut self) -> io::Result<bool> {
@@ -98,6 +219,10 @@ impl<R: Read> LogTrimmerReader<R> {
         let mut truncated_bytes: u64 = 0;
 
         loop {
+            if self.is_cancelled() {
+                return Ok(false);
+            }
+
             let available = self.input.fill_buf()?;
             if available.is_empty() {
                 return Ok(!self.internal_buf.is_empty());
@@ -145,7 +270,7 @@ impl<R: Read> LogTrimmerReader<R> {
                     if self.truncate_enabled {
                         let remaining = self.truncate_at_bytes.saturating_sub(current_msg_len);
                         if bytes_until_newline > remaining {
-                            take = remaining;
+                            take = self.adjust_take_to_utf8_boundary(search_slice, remaining);
                             truncated = true;
                         }
                     }
@@ -210,6 +335,10 @@ impl<R: Read> LogTrimmerReader<R> {
 
         // Read a single line using fill_buf + memchr to avoid per-call allocation churn.
         loop {
+            if self.is_cancelled() {
+                return Ok(false);
+            }
+
             let available = self.input.fill_buf()?;
             if available.is_empty() {
                 if self.docker_line_buf.is_empty() {
@@ -265,12 +394,12 @@ impl<R: Read> LogTrimmerReader<R> {
             return Ok(true);
         }
 
-        let truncated_bytes = (message.len() - self.truncate_at_bytes) as u64;
+        let take = self.adjust_take_to_utf8_boundary(message, self.truncate_at_bytes);
+        let truncated_bytes = (message.len() - take) as u64;
 
         self.internal_buf
             .extend_from_slice(&self.docker_line_buf[..PREFIX.len()]);
-        self.internal_buf
-            .extend_from_slice(&message[..self.truncate_at_bytes]);
+        self.internal_buf.extend_from_slice(&message[..take]);
         Self::append_truncation_marker_json(&mut self.internal_buf, truncated_bytes);
         self.internal_buf
             .extend_from_slice(&self.docker_line_buf[msg_end..]);
@@ -299,10 +428,21 @@ impl<R: Read> LogTrimmerReader<R> {
 
     /// Helper: Consumes bytes until a newline or EOF, returning the count of bytes discarded
     /// and whether a newline was encountered.
+    /// Gives a caller access to the wrapped reader, e.g. to reach a
+    /// [`CriLineReassemblingReader`]'s [`CriLineReassemblingReader::flush_pending`] before
+    /// shutting the stream down.
+    pub fn get_inner_mut(&mut self) -> &mut R {
+        self.input.get_mut()
+    }
+
     fn discard_rest_of_line(&mut self) -> io::Result<(usize, bool)> {
         let mut total_discarded = 0;
 
         loop {
+            if self.is_cancelled() {
+                return Ok((total_discarded, false));
+            }
+
             let available = self.input.fill_buf()?;
             if available.is_empty() {
                 return Ok((total_discarded, false));
@@ -323,6 +463,211 @@ impl<R: Read> LogTrimmerReader<R> {
 }
 
 impl<R: Read> Read for LogTrimmerReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let to_copy = std::cmp::min(available.len(), buf.len());
+
+        buf[..to_copy].copy_from_slice(&available[..to_copy]);
+        self.consume(to_copy);
+
+        Ok(to_copy)
+    }
+
+    /// Overrides the default `read`-in-a-loop impl: the generic one would drive `buf`-sized
+    /// `read` calls that each `memcpy` a slice of `internal_buf` into the caller's buffer and
+    /// then immediately `refill_buffer` again once it's drained, one (often much larger)
+    /// per-line `memcpy` away from the same result. Draining the whole stream is the common case
+    /// (`stream_forward`/`stream_backward` read every line), so this instead appends
+    /// `internal_buf` onto `buf` directly per line and lets `refill_buffer` keep resetting `pos`
+    /// for us, skipping the copy-into-caller-slice-then-track-`pos` bookkeeping entirely.
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let start_len = buf.len();
+
+        if self.pos < self.internal_buf.len() {
+            buf.extend_from_slice(&self.internal_buf[self.pos..]);
+            self.pos = self.internal_buf.len();
+        }
+
+        while self.refill_buffer()? {
+            buf.extend_from_slice(&self.internal_buf);
+            self.pos = self.internal_buf.len();
+        }
+
+        Ok(buf.len() - start_len)
+    }
+
+    // `Read::read_buf` would let this skip the `internal_buf` -> `Vec` copy too, but it's still
+    // gated behind the unstable `read_buf` feature -- not something this crate can take on stable.
+}
+
+impl<R: Read> BufRead for LogTrimmerReader<R> {
+    /// Mirrors `std::io::BufReader::fill_buf`: refills `internal_buf` from scratch (via
+    /// `refill_buffer`) once it's fully consumed, then hands back a borrow of whatever's left
+    /// unread in it, so a caller driving `read_until`/`lines` over a already-trimmed/reassembled
+    /// line never forces the copy into an intermediate `buf` that `Read::read` does.
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.internal_buf.len() {
+            self.refill_buffer()?;
+        }
+
+        Ok(&self.internal_buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = std::cmp::min(self.pos + amt, self.internal_buf.len());
+    }
+}
+
+/// Splits a raw CRI log line into its `(timestamp, stream, tag, fragment)` parts, the same
+/// fixed-width parse `process_output` uses downstream (`stdout`/`stderr` are both exactly 6
+/// bytes). `fragment` has any trailing `\n`/`\r` already trimmed. Returns `None` if `line` doesn't
+/// look like a CRI record, so the caller can pass it through unchanged instead of dropping it.
+fn parse_cri_line(line: &[u8]) -> Option<(&[u8], &[u8], &[u8], &[u8])> {
+    let space = memchr(b' ', line)?;
+    let (timestamp, rest) = (&line[..space], &line[space + 1..]);
+
+    if rest.len() < 9 || rest[6] != b' ' || rest[8] != b' ' {
+        return None;
+    }
+
+    let stream = &rest[0..6];
+    let tag = &rest[7..8];
+
+    let mut fragment_end = rest.len();
+    while fragment_end > 9 && matches!(rest[fragment_end - 1], b'\n' | b'\r') {
+        fragment_end -= 1;
+    }
+
+    Some((timestamp, stream, tag, &rest[9..fragment_end]))
+}
+
+/// Reassembles kubelet-split CRI log lines before they reach the grep searcher, so a pattern
+/// spanning a `P`/`F` split point can still match, and so `LogTrimmerReader`'s `truncate_at_bytes`
+/// (layered on top of this reader) is checked against the reassembled message length rather than
+/// each fragment's.
+///
+/// The kubelet splits a long container log line across consecutive `P` ("partial") records
+/// sharing the same stream, terminated by an `F` ("full") record; each fragment carries its own
+/// timestamp. This reader buffers those fragments per stream and, once the terminating `F` record
+/// arrives, emits a single `<first timestamp> <stream> F <message>\n` line using the first
+/// fragment's timestamp -- the same reassembly `process_output`'s `CriLineReassembler` does, just
+/// moved ahead of the searcher instead of after it. A non-CRI format, or a line that doesn't
+/// parse as a CRI record, passes through unchanged. A stream whose `P`-run exceeds
+/// [`CRI_REASSEMBLY_MAX_PENDING_BYTES`] is emitted early rather than buffered indefinitely waiting
+/// for an `F` that may never come; see [`CriLineReassemblingReverseReader`] for the backward-read
+/// counterpart used by `stream_backward`.
+#[derive(Debug)]
+pub struct CriLineReassemblingReader<R> {
+    input: BufReader<R>,
+    format: FileFormat,
+    // Keyed by stream ("stdout"/"stderr"); value is (first fragment's timestamp, concatenated
+    // message bytes so far).
+    pending: HashMap<Vec<u8>, (Vec<u8>, Vec<u8>)>,
+    internal_buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> CriLineReassemblingReader<R> {
+    pub fn new(reader: R, format: FileFormat) -> Self {
+        Self {
+            input: BufReader::with_capacity(LOG_TRIMMER_READER_BUFFER_SIZE, reader),
+            format,
+            pending: HashMap::new(),
+            internal_buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Forces out any partial fragments still buffered (one combined line per stream that never
+    /// saw its terminating `F` record), so a caller that's about to stop reading -- e.g.
+    /// `listen_for_changes` reacting to cancellation -- doesn't silently lose a message that was
+    /// mid-split. Returns `None` once nothing is left pending.
+    pub fn flush_pending(&mut self) -> Option<Vec<u8>> {
+        let stream = self.pending.keys().next().cloned()?;
+        let (timestamp, message) = self.pending.remove(&stream)?;
+        Some(Self::render(&timestamp, &stream, &message))
+    }
+
+    fn render(timestamp: &[u8], stream: &[u8], message: &[u8]) -> Vec<u8> {
+        let mut line = Vec::with_capacity(timestamp.len() + stream.len() + message.len() + 5);
+        line.extend_from_slice(timestamp);
+        line.push(b' ');
+        line.extend_from_slice(stream);
+        line.extend_from_slice(b" F ");
+        line.extend_from_slice(message);
+        line.push(b'\n');
+        line
+    }
+
+    fn read_raw_line(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        let n = self.input.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+
+    fn refill_buffer(&mut self) -> io::Result<bool> {
+        self.internal_buf.clear();
+        self.pos = 0;
+
+        if !matches!(self.format, FileFormat::CRI) {
+            return match self.read_raw_line()? {
+                Some(line) => {
+                    self.internal_buf = line;
+                    Ok(true)
+                }
+                None => Ok(false),
+            };
+        }
+
+        loop {
+            // Note: for a plain file being tailed, "no bytes available right now" isn't the same
+            // as "no more bytes ever" -- the kubelet may still be mid-write on the terminating `F`
+            // record. So unlike `flush_pending`, reaching the end of what's currently readable
+            // does NOT flush a pending fragment; it's kept buffered across calls until either an
+            // `F` record completes it or the caller explicitly flushes it (e.g. on shutdown).
+            let Some(raw_line) = self.read_raw_line()? else {
+                return Ok(false);
+            };
+
+            match parse_cri_line(&raw_line) {
+                Some((timestamp, stream, tag, fragment)) if tag == b"P" => {
+                    let entry = self
+                        .pending
+                        .entry(stream.to_vec())
+                        .or_insert_with(|| (timestamp.to_vec(), Vec::new()));
+                    entry.1.extend_from_slice(fragment);
+
+                    if entry.1.len() >= CRI_REASSEMBLY_MAX_PENDING_BYTES {
+                        let stream = stream.to_vec();
+                        let (timestamp, message) = self.pending.remove(&stream).unwrap();
+                        self.internal_buf = Self::render(&timestamp, &stream, &message);
+                        return Ok(true);
+                    }
+                }
+                Some((timestamp, stream, _tag, fragment)) => {
+                    let (first_timestamp, mut message) = self
+                        .pending
+                        .remove(stream)
+                        .unwrap_or_else(|| (timestamp.to_vec(), Vec::new()));
+                    message.extend_from_slice(fragment);
+                    self.internal_buf = Self::render(&first_timestamp, stream, &message);
+                    return Ok(true);
+                }
+                None => {
+                    // Doesn't look like a CRI record; pass it through unchanged rather than
+                    // dropping it.
+                    self.internal_buf = raw_line;
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for CriLineReassemblingReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         while self.pos >= self.internal_buf.len() {
             let has_more = self.refill_buffer()?;
@@ -352,10 +697,15 @@ pub struct ReverseLineReader<R: Read + Seek> {
     buf_end: usize,        // current valid end index in the buffer
     line_buf: Vec<u8>,     // accumulates bytes for a line spanning chunks (stored in reverse order)
     current_line: Vec<u8>, // the next line (in correct order) waiting to be read
+    delimiter: u8,
+    strip_cr: bool,
+    cancel_token: Option<CancellationToken>,
 }
 
 impl<R: Read + Seek> ReverseLineReader<R> {
-    /// Creates a new ReverseLineReader wrapping a seekable reader.
+    /// Creates a new ReverseLineReader wrapping a seekable reader. Lines are split on `b'\n'`
+    /// with the delimiter kept at the end, unless overridden via [`Self::with_delimiter`] /
+    /// [`Self::with_strip_cr`].
     pub fn new(mut inner: R, min_pos: u64, max_pos: u64) -> io::Result<Self> {
         let pos = inner.seek(SeekFrom::Start(max_pos))?;
         Ok(Self {
@@ -367,9 +717,53 @@ impl<R: Read + Seek> ReverseLineReader<R> {
             buf_end: 0,
             line_buf: Vec::new(),
             current_line: Vec::new(),
+            delimiter: b'\n',
+            strip_cr: false,
+            cancel_token: None,
         })
     }
 
+    /// Opts into splitting lines on `delimiter` instead of `b'\n'` -- e.g. shippers that frame
+    /// records with something other than a bare newline. Defaults to `b'\n'` to keep existing
+    /// callers' behavior.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Opts into stripping a trailing `\r` (CRLF line endings, as written by Windows nodes or
+    /// certain log shippers) from each line before it's returned. Off by default to keep the
+    /// existing byte-exact behavior for callers relying on it.
+    pub fn with_strip_cr(mut self, strip: bool) -> Self {
+        self.strip_cr = strip;
+        self
+    }
+
+    /// Pops a trailing `\r` off `line` when [`Self::with_strip_cr`] is enabled. Called right
+    /// before the delimiter (or nothing, for the first line in the file) is appended -- a CRLF
+    /// record's `\r` is always already the last byte assembled into `line` by this point,
+    /// whether it came from the current chunk or was carried across a chunk boundary via
+    /// `line_buf`, so no extra lookahead across `fill_buf` calls is needed.
+    fn strip_trailing_cr(&self, line: &mut Vec<u8>) {
+        if self.strip_cr && line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    }
+
+    /// Opts into checking `token` for cancellation at the top of every `next_line` loop
+    /// iteration, so scanning a pathologically long or delimiter-free span of the file can't
+    /// hold this reader hostage past a caller's cancellation. Not set by default, since most
+    /// callers already sit behind a [`TermReader`] and don't need a second check here.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
     /// Fills the internal buffer by reading a chunk from the file.
     /// Returns Ok(true) if a chunk was read, or Ok(false) if at the beginning.
     fn fill_buf(&mut self) -> io::Result<bool> {
@@ -389,12 +783,18 @@ impl<R: Read + Seek> ReverseLineReader<R> {
     }
 
     /// Retrieves the next line (as a Vec<u8>) in proper order.
-    /// Lines are determined by the newline character (`b'\n'`). The newline is kept at the end.
+    /// Lines are determined by `self.delimiter` (`b'\n'` unless overridden via
+    /// [`Self::with_delimiter`]). The delimiter is kept at the end, unless stripped by
+    /// [`Self::with_strip_cr`] as a trailing `\r`.
     fn next_line(&mut self) -> io::Result<Option<Vec<u8>>> {
         loop {
+            if self.is_cancelled() {
+                return Ok(None);
+            }
+
             if self.buf_end > self.buf_start {
                 if let Some(newline_offset) =
-                    memchr::memrchr(b'\n', &self.buf[self.buf_start..self.buf_end])
+                    memchr::memrchr(self.delimiter, &self.buf[self.buf_start..self.buf_end])
                 {
                     let newline_pos = self.buf_start + newline_offset;
                     // If the newline is the last byte in the buffer...
@@ -407,7 +807,8 @@ impl<R: Read + Seek> ReverseLineReader<R> {
                             // If there's accumulated data, form the line and append the newline.
                             let mut line = self.line_buf.clone();
                             line.reverse();
-                            line.push(b'\n');
+                            self.strip_trailing_cr(&mut line);
+                            line.push(self.delimiter);
                             self.line_buf.clear();
                             self.buf_end = newline_pos;
                             return Ok(Some(line));
@@ -424,7 +825,8 @@ impl<R: Read + Seek> ReverseLineReader<R> {
                             self.line_buf.clear();
                         }
                         self.buf_end = newline_pos;
-                        line_part.push(b'\n');
+                        self.strip_trailing_cr(&mut line_part);
+                        line_part.push(self.delimiter);
                         return Ok(Some(line_part));
                     }
                 } else {
@@ -443,6 +845,7 @@ impl<R: Read + Seek> ReverseLineReader<R> {
                     let mut line = self.line_buf.clone();
                     line.reverse();
                     self.line_buf.clear();
+                    self.strip_trailing_cr(&mut line);
                     return Ok(Some(line));
                 }
             }
@@ -454,6 +857,7 @@ impl<R: Read + Seek> ReverseLineReader<R> {
                     let mut line = self.line_buf.clone();
                     line.reverse();
                     self.line_buf.clear();
+                    self.strip_trailing_cr(&mut line);
                     return Ok(Some(line));
                 }
             }
@@ -482,6 +886,191 @@ impl<R: Read + Seek> Read for ReverseLineReader<R> {
         }
         Ok(total_written)
     }
+
+    /// Overrides the default `read`-in-a-loop impl for the same reason as
+    /// [`LogTrimmerReader::read_to_end`]: appends each `next_line` straight onto `buf` instead of
+    /// draining `current_line` through repeated small `read` calls.
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let start_len = buf.len();
+
+        if !self.current_line.is_empty() {
+            buf.append(&mut self.current_line);
+        }
+
+        while let Some(line) = self.next_line()? {
+            buf.extend_from_slice(&line);
+        }
+
+        Ok(buf.len() - start_len)
+    }
+}
+
+/// Reverse counterpart to [`CriLineReassemblingReader`]: wraps a reader that already yields CRI
+/// log lines in reverse chronological order (a [`ReverseLineReader`], in practice) and reassembles
+/// kubelet-split `P`/`F` runs walking them back-to-front instead of front-to-back.
+///
+/// Where the forward reader sees each run's `P` fragments first and finalizes on the terminating
+/// `F`, this one sees the `F` first and prepends each preceding `P` fragment as it arrives,
+/// finalizing once it hits a line that isn't a continuation of the same stream's run -- another
+/// record for that stream, or the end of the readable range. That boundary line can't be folded
+/// into the line just finalized, so it's held and replayed as the next line on the following call
+/// instead of being dropped. A stream still pending when the readable range runs out (its `P`-run
+/// started before `min_pos`) is flushed the same way [`CriLineReassemblingReader::flush_pending`]
+/// lets a forward caller flush on shutdown -- except here it happens automatically at EOF, since
+/// `stream_backward`'s reverse read is a single bounded pass rather than a live tail.
+#[derive(Debug)]
+pub struct CriLineReassemblingReverseReader<R> {
+    input: BufReader<R>,
+    format: FileFormat,
+    // Keyed by stream ("stdout"/"stderr"); value is (earliest fragment's timestamp seen so far,
+    // message bytes accumulated in correct chronological order).
+    pending: HashMap<Vec<u8>, (Vec<u8>, Vec<u8>)>,
+    // A raw line read to check whether it continues some stream's pending run, but that turned
+    // out to belong to a different run -- replayed on the next `refill_buffer` call.
+    held: Option<Vec<u8>>,
+    internal_buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> CriLineReassemblingReverseReader<R> {
+    pub fn new(reader: R, format: FileFormat) -> Self {
+        Self {
+            input: BufReader::with_capacity(LOG_TRIMMER_READER_BUFFER_SIZE, reader),
+            format,
+            pending: HashMap::new(),
+            held: None,
+            internal_buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn render(timestamp: &[u8], stream: &[u8], message: &[u8]) -> Vec<u8> {
+        let mut line = Vec::with_capacity(timestamp.len() + stream.len() + message.len() + 5);
+        line.extend_from_slice(timestamp);
+        line.push(b' ');
+        line.extend_from_slice(stream);
+        line.extend_from_slice(b" F ");
+        line.extend_from_slice(message);
+        line.push(b'\n');
+        line
+    }
+
+    fn read_raw_line(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if let Some(line) = self.held.take() {
+            return Ok(Some(line));
+        }
+
+        let mut line = Vec::new();
+        let n = self.input.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+
+    /// Flushes one arbitrary stream's still-pending run. `refill_buffer` already calls this at EOF
+    /// so a caller normally doesn't need to reach for it directly; it's exposed for the same
+    /// shutdown-time use [`CriLineReassemblingReader::flush_pending`] serves in the forward case.
+    pub fn flush_pending(&mut self) -> Option<Vec<u8>> {
+        let stream = self.pending.keys().next().cloned()?;
+        let (timestamp, message) = self.pending.remove(&stream)?;
+        Some(Self::render(&timestamp, &stream, &message))
+    }
+
+    fn refill_buffer(&mut self) -> io::Result<bool> {
+        self.internal_buf.clear();
+        self.pos = 0;
+
+        if !matches!(self.format, FileFormat::CRI) {
+            return match self.read_raw_line()? {
+                Some(line) => {
+                    self.internal_buf = line;
+                    Ok(true)
+                }
+                None => Ok(false),
+            };
+        }
+
+        loop {
+            let Some(raw_line) = self.read_raw_line()? else {
+                return match self.flush_pending() {
+                    Some(line) => {
+                        self.internal_buf = line;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                };
+            };
+
+            match parse_cri_line(&raw_line) {
+                Some((timestamp, stream, tag, fragment)) if tag == b"P" => {
+                    let stream_key = stream.to_vec();
+                    let timestamp = timestamp.to_vec();
+                    let fragment = fragment.to_vec();
+
+                    match self.pending.get_mut(&stream_key) {
+                        Some(entry) => {
+                            let mut combined = fragment;
+                            combined.extend_from_slice(&entry.1);
+                            entry.1 = combined;
+                            entry.0 = timestamp;
+
+                            if entry.1.len() >= CRI_REASSEMBLY_MAX_PENDING_BYTES {
+                                let (timestamp, message) =
+                                    self.pending.remove(&stream_key).unwrap();
+                                self.internal_buf = Self::render(&timestamp, &stream_key, &message);
+                                return Ok(true);
+                            }
+                        }
+                        None => {
+                            // This `P`'s terminating `F` lies outside the readable range (e.g.
+                            // past `start_time`), so there's no run to continue. Pass it through
+                            // unchanged rather than dropping it.
+                            self.internal_buf = raw_line;
+                            return Ok(true);
+                        }
+                    }
+                }
+                Some((timestamp, stream, _tag, fragment)) => {
+                    let stream_key = stream.to_vec();
+                    let previous = self.pending.remove(&stream_key);
+                    self.pending
+                        .insert(stream_key.clone(), (timestamp.to_vec(), fragment.to_vec()));
+
+                    if let Some((prev_timestamp, prev_message)) = previous {
+                        // This record is the boundary for the run that was accumulating: that run
+                        // is complete, so finalize and emit it. This record starts a new run of
+                        // its own, already inserted above.
+                        self.internal_buf = Self::render(&prev_timestamp, &stream_key, &prev_message);
+                        return Ok(true);
+                    }
+                }
+                None => {
+                    self.internal_buf = raw_line;
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for CriLineReassemblingReverseReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.internal_buf.len() {
+            let has_more = self.refill_buffer()?;
+            if !has_more {
+                return Ok(0);
+            }
+        }
+
+        let available = self.internal_buf.len() - self.pos;
+        let to_copy = std::cmp::min(available, buf.len());
+
+        buf[..to_copy].copy_from_slice(&self.internal_buf[self.pos..self.pos + to_copy]);
+        self.pos += to_copy;
+
+        Ok(to_copy)
+    }
 }
 
 #[cfg(test)]
@@ -551,6 +1140,35 @@ mod tests {
         Ok(())
     }
 
+    // "ab\u{e9}" (2-byte), "a\u{20ac}" (3-byte), and "ab\u{1f600}" (4-byte) each put a multibyte
+    // character's lead byte right at the naive cut point, so a byte-exact truncation would split
+    // it; `with_utf8_safe_truncation` must back the whole character off instead.
+    #[rstest]
+    #[case(3, "2024-11-20T10:00:00Z stdout F ab\u{e9}\n", "ab", 2)]
+    #[case(2, "2024-11-20T10:00:00Z stdout F a\u{20ac}\n", "a", 3)]
+    #[case(3, "2024-11-20T10:00:00Z stdout F a\u{20ac}\n", "a", 3)]
+    #[case(4, "2024-11-20T10:00:00Z stdout F ab\u{1f600}\n", "ab", 4)]
+    fn log_trimmer_reader_utf8_safe_truncation_never_splits_a_character_cri(
+        #[case] limit: u64,
+        #[case] input: &str,
+        #[case] kept_message: &str,
+        #[case] truncated_bytes: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut reader =
+            LogTrimmerReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI, limit)
+                .with_utf8_safe_truncation(true);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        let mut expected = format!("2024-11-20T10:00:00Z stdout F {kept_message}").into_bytes();
+        expected.extend_from_slice(&truncated_bytes.to_be_bytes());
+        expected.push(TRUNCATION_SENTINEL);
+        expected.push(b'\n');
+
+        assert_eq!(output, expected);
+        Ok(())
+    }
+
     #[rstest]
     #[case(
         3,
@@ -607,6 +1225,326 @@ mod tests {
         Ok(())
     }
 
+    // Same boundary-straddling characters as the CRI case above, embedded raw (unescaped) in the
+    // JSON "log" field the way the Docker log driver writes non-ASCII text.
+    #[rstest]
+    #[case(3, "ab\u{e9}", "ab", 2)]
+    #[case(2, "a\u{20ac}", "a", 3)]
+    #[case(4, "ab\u{1f600}", "ab", 4)]
+    fn log_trimmer_reader_utf8_safe_truncation_never_splits_a_character_docker(
+        #[case] limit: u64,
+        #[case] message: &str,
+        #[case] kept_message: &str,
+        #[case] truncated_bytes: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        let input =
+            format!(r#"{{"log":"{message}","stream":"stdout","time":"2024-11-20T10:00:00Z"}}"#);
+        let input_with_newline = format!("{input}\n").into_bytes();
+        let mut reader =
+            LogTrimmerReader::new(Cursor::new(input_with_newline), FileFormat::Docker, limit)
+                .with_utf8_safe_truncation(true);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        let mut expected = format!(r#"{{"log":"{kept_message}"#).into_bytes();
+        LogTrimmerReader::<&[u8]>::append_truncation_marker_json(&mut expected, truncated_bytes);
+        expected.extend_from_slice(br#"","stream":"stdout","time":"2024-11-20T10:00:00Z"}"#);
+        expected.push(b'\n');
+
+        assert_eq!(output, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn log_trimmer_reader_buf_read_matches_read_for_cri() -> Result<(), Box<dyn Error>> {
+        let input =
+            "2024-11-20T10:00:00Z stdout F 1234567890\n2024-11-21T10:00:00Z stdout F abcdef\n";
+
+        let mut via_read =
+            LogTrimmerReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI, 5);
+        let mut expected = Vec::new();
+        via_read.read_to_end(&mut expected)?;
+
+        let mut via_buf_read =
+            LogTrimmerReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI, 5);
+        let mut actual = Vec::new();
+        loop {
+            let available = via_buf_read.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+            actual.extend_from_slice(available);
+            let consumed = available.len();
+            via_buf_read.consume(consumed);
+        }
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn log_trimmer_reader_buf_read_matches_read_for_docker() -> Result<(), Box<dyn Error>> {
+        let input = br#"{"log":"abcdefghij","stream":"stdout","time":"2024-11-20T10:00:00Z"}
+{"log":"klmnop","stream":"stdout","time":"2024-11-21T10:00:00Z"}
+"#;
+
+        let mut via_read = LogTrimmerReader::new(Cursor::new(input), FileFormat::Docker, 5);
+        let mut expected = Vec::new();
+        via_read.read_to_end(&mut expected)?;
+
+        let mut via_buf_read = LogTrimmerReader::new(Cursor::new(input), FileFormat::Docker, 5);
+        let mut actual = Vec::new();
+        loop {
+            let available = via_buf_read.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+            actual.extend_from_slice(available);
+            let consumed = available.len();
+            via_buf_read.consume(consumed);
+        }
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn log_trimmer_reader_consume_supports_partial_reads() -> Result<(), Box<dyn Error>> {
+        let input = "2024-11-20T10:00:00Z stdout F hello\n";
+        let mut reader = LogTrimmerReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI, 0);
+
+        let first_byte = reader.fill_buf()?[0];
+        assert_eq!(first_byte, b'2');
+        reader.consume(1);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest)?;
+        assert_eq!(rest, input.as_bytes()[1..].to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn log_trimmer_reader_truncates_reassembled_cri_partial_run() -> Result<(), Box<dyn Error>> {
+        // "helloworld" arrives as two P fragments before the terminating F; truncating the
+        // CriLineReassemblingReader-joined line (limit 5) must land on the full 10-byte message,
+        // not on either individual fragment, and produce exactly one truncation marker.
+        let input = "2024-11-20T10:00:00Z stdout P hello\n2024-11-20T10:00:01Z stdout F world\n";
+        let reassembling =
+            CriLineReassemblingReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+        let mut reader = LogTrimmerReader::new(reassembling, FileFormat::CRI, 5);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        let mut expected = b"2024-11-20T10:00:00Z stdout F hello".to_vec();
+        expected.extend_from_slice(&5u64.to_be_bytes());
+        expected.push(TRUNCATION_SENTINEL);
+        expected.push(b'\n');
+
+        assert_eq!(output, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn log_trimmer_reader_reassembles_interleaved_streams_before_truncating()
+    -> Result<(), Box<dyn Error>> {
+        let input = "2024-11-20T10:00:00Z stdout P ab\n2024-11-20T10:00:00Z stderr P cd\n\
+                     2024-11-20T10:00:01Z stdout F cdef\n2024-11-20T10:00:01Z stderr F efgh\n";
+        let reassembling =
+            CriLineReassemblingReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+        let mut reader = LogTrimmerReader::new(reassembling, FileFormat::CRI, 0);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("stdout F abcdef\n"));
+        assert!(output.contains("stderr F cdefgh\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn log_trimmer_reader_exposes_flush_pending_for_unterminated_cri_partial_at_eof()
+    -> Result<(), Box<dyn Error>> {
+        // A P run with no terminating F is buffered indefinitely rather than auto-flushed at
+        // physical EOF (a live-tailed file may still be mid-write on the F record) -- so, same as
+        // `stream_forward` does on shutdown, the caller reaches through `get_inner_mut` to the
+        // wrapped `CriLineReassemblingReader` to flush it explicitly once it knows no more is
+        // coming, and truncation then applies to that flushed (and still reassembled) message.
+        let input = "2024-11-20T10:00:00Z stdout P hello\n";
+        let reassembling =
+            CriLineReassemblingReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+        let mut reader = LogTrimmerReader::new(reassembling, FileFormat::CRI, 0);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+        assert!(output.is_empty());
+
+        let flushed = reader
+            .get_inner_mut()
+            .flush_pending()
+            .expect("pending P run should flush");
+        assert_eq!(flushed, b"2024-11-20T10:00:00Z stdout F hello\n");
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reader_joins_partial_fragments() -> Result<(), Box<dyn Error>> {
+        let input = "2024-11-20T10:00:00Z stdout P hel\n2024-11-20T10:00:01Z stdout F lo\n";
+        let mut reader =
+            CriLineReassemblingReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        // The first fragment's timestamp is preserved, and the F record's is discarded.
+        assert_eq!(output, b"2024-11-20T10:00:00Z stdout F hello\n");
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reader_keeps_streams_independent() -> Result<(), Box<dyn Error>> {
+        let input = "2024-11-20T10:00:00Z stdout P out-\n\
+                     2024-11-20T10:00:00Z stderr F err\n\
+                     2024-11-20T10:00:01Z stdout F frag\n";
+        let mut reader =
+            CriLineReassemblingReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        assert_eq!(
+            output,
+            b"2024-11-20T10:00:00Z stderr F err\n2024-11-20T10:00:00Z stdout F out-frag\n".to_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reader_flushes_unterminated_partial_on_demand() {
+        let input = "2024-11-20T10:00:00Z stdout P hel\n";
+        let mut reader =
+            CriLineReassemblingReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+
+        // Nothing to read yet: the only record is a P fragment with no terminating F.
+        let mut buf = [0u8; 1];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        let flushed = reader.flush_pending().expect("should flush the pending fragment");
+        assert_eq!(flushed, b"2024-11-20T10:00:00Z stdout F hel\n");
+        assert!(reader.flush_pending().is_none());
+    }
+
+    #[test]
+    fn cri_line_reassembling_reader_passes_through_docker_format_unchanged() -> Result<(), Box<dyn Error>>
+    {
+        let input = r#"{"log":"hello\n","stream":"stdout","time":"2024-11-20T10:00:00Z"}"#;
+        let input_with_newline = format!("{input}\n");
+        let mut reader = CriLineReassemblingReader::new(
+            Cursor::new(input_with_newline.clone().into_bytes()),
+            FileFormat::Docker,
+        );
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        assert_eq!(output, input_with_newline.into_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reader_caps_pending_size() -> Result<(), Box<dyn Error>> {
+        // The second fragment pushes the run's accumulated size past the cap, so it's emitted
+        // right away with whatever's been collected instead of waiting for an `F` that never
+        // arrives. The first fragment's timestamp is still what's carried, same as a normal run.
+        let fragment = "a".repeat(CRI_REASSEMBLY_MAX_PENDING_BYTES - 2);
+        let input =
+            format!("2024-11-20T10:00:00Z stdout P {fragment}\n2024-11-20T10:00:01Z stdout P more\n");
+        let mut reader = CriLineReassemblingReader::new(Cursor::new(input.into_bytes()), FileFormat::CRI);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        let expected = format!("2024-11-20T10:00:00Z stdout F {fragment}more\n");
+        assert_eq!(output, expected.into_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reverse_reader_joins_partial_fragments() -> Result<(), Box<dyn Error>> {
+        // ReverseLineReader yields lines in reverse chronological order, so the `F` arrives first.
+        let input = "2024-11-20T10:00:01Z stdout F lo\n2024-11-20T10:00:00Z stdout P hel\n";
+        let mut reader =
+            CriLineReassemblingReverseReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        // The earliest (`P`) fragment's timestamp wins, and the payloads are joined in
+        // chronological order even though they arrived in reverse.
+        assert_eq!(output, b"2024-11-20T10:00:00Z stdout F hello\n");
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reverse_reader_keeps_streams_independent() -> Result<(), Box<dyn Error>> {
+        let input = "2024-11-20T10:00:01Z stdout F frag\n\
+                     2024-11-20T10:00:00Z stderr F err\n\
+                     2024-11-20T10:00:00Z stdout P out-\n";
+        let mut reader =
+            CriLineReassemblingReverseReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        // Both streams' runs are only known to be complete once the readable range is exhausted,
+        // so they're flushed together at EOF in whatever order the pending map happens to
+        // iterate -- compare as a set rather than asserting a specific order.
+        let mut lines: Vec<&[u8]> = output.split_inclusive(|&b| b == b'\n').collect();
+        lines.sort_unstable();
+        let mut expected: Vec<&[u8]> = vec![
+            b"2024-11-20T10:00:00Z stderr F err\n",
+            b"2024-11-20T10:00:00Z stdout F out-frag\n",
+        ];
+        expected.sort_unstable();
+        assert_eq!(lines, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reverse_reader_flushes_unterminated_partial_at_eof() -> Result<(), Box<dyn Error>>
+    {
+        // No `F` ever precedes this `P` in the reverse stream: its run started before the
+        // readable range began, so EOF flushes what was collected instead of dropping it.
+        let input = "2024-11-20T10:00:00Z stdout P hel\n";
+        let mut reader =
+            CriLineReassemblingReverseReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI);
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        assert_eq!(output, b"2024-11-20T10:00:00Z stdout F hel\n");
+        Ok(())
+    }
+
+    #[test]
+    fn cri_line_reassembling_reverse_reader_passes_through_docker_format_unchanged()
+    -> Result<(), Box<dyn Error>> {
+        let input = r#"{"log":"hello\n","stream":"stdout","time":"2024-11-20T10:00:00Z"}"#;
+        let input_with_newline = format!("{input}\n");
+        let mut reader = CriLineReassemblingReverseReader::new(
+            Cursor::new(input_with_newline.clone().into_bytes()),
+            FileFormat::Docker,
+        );
+
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+
+        assert_eq!(output, input_with_newline.into_bytes());
+        Ok(())
+    }
+
     #[test]
     fn test_reverse_line_reader() -> Result<(), Box<dyn Error>> {
         // Write file
@@ -643,4 +1581,237 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn log_trimmer_reader_read_to_end_matches_generic_small_reads() -> Result<(), Box<dyn Error>> {
+        // "Generic small reads" stands in for the default `Read::read_to_end` impl (repeated
+        // small `read` calls into a fixed buffer) that the override above replaces.
+        let input =
+            "2024-11-20T10:00:00Z stdout F 1234567890\n2024-11-21T10:00:00Z stdout F abcdef\n";
+
+        let mut via_override =
+            LogTrimmerReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI, 5);
+        let mut actual = Vec::new();
+        via_override.read_to_end(&mut actual)?;
+
+        let mut via_small_reads =
+            LogTrimmerReader::new(Cursor::new(input.as_bytes()), FileFormat::CRI, 5);
+        let mut expected = Vec::new();
+        let mut chunk = [0u8; 3];
+        loop {
+            let n = via_small_reads.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            expected.extend_from_slice(&chunk[..n]);
+        }
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_line_reader_read_to_end_matches_generic_small_reads() -> Result<(), Box<dyn Error>> {
+        let input = b"line one\nline two\nline three\n";
+
+        let mut via_override =
+            ReverseLineReader::new(Cursor::new(&input[..]), 0, input.len() as u64)?;
+        let mut actual = Vec::new();
+        via_override.read_to_end(&mut actual)?;
+
+        let mut via_small_reads =
+            ReverseLineReader::new(Cursor::new(&input[..]), 0, input.len() as u64)?;
+        let mut expected = Vec::new();
+        let mut chunk = [0u8; 3];
+        loop {
+            let n = via_small_reads.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            expected.extend_from_slice(&chunk[..n]);
+        }
+
+        assert_eq!(actual, expected);
+        assert_eq!(actual, b"line three\nline two\nline one\n");
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_line_reader_default_keeps_cr_and_uses_lf_delimiter() -> Result<(), Box<dyn Error>> {
+        let input = b"one\r\ntwo\nthree\r\n";
+        let mut reader = ReverseLineReader::new(Cursor::new(&input[..]), 0, input.len() as u64)?;
+
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line()? {
+            lines.push(line);
+        }
+
+        assert_eq!(
+            lines,
+            vec![b"three\r\n".to_vec(), b"two\n".to_vec(), b"one\r\n".to_vec()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_line_reader_with_strip_cr_removes_trailing_cr_from_mixed_endings(
+    ) -> Result<(), Box<dyn Error>> {
+        let input = b"one\r\ntwo\nthree\r\n";
+        let mut reader = ReverseLineReader::new(Cursor::new(&input[..]), 0, input.len() as u64)?
+            .with_strip_cr(true);
+
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line()? {
+            lines.push(line);
+        }
+
+        assert_eq!(lines, vec![b"three\n".to_vec(), b"two\n".to_vec(), b"one\n".to_vec()]);
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_line_reader_with_delimiter_splits_on_configured_byte(
+    ) -> Result<(), Box<dyn Error>> {
+        let input = b"one\x00two\x00three\x00";
+        let mut reader = ReverseLineReader::new(Cursor::new(&input[..]), 0, input.len() as u64)?
+            .with_delimiter(0);
+
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line()? {
+            lines.push(line);
+        }
+
+        assert_eq!(
+            lines,
+            vec![b"three\x00".to_vec(), b"two\x00".to_vec(), b"one\x00".to_vec()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_line_reader_strips_cr_split_across_chunk_boundary() -> Result<(), Box<dyn Error>> {
+        // Lays out three lines so the `\r`/`\n` of the middle line's CRLF ending land in
+        // different `REVERSE_READER_CHUNK_SIZE`-sized chunks: the trailing `\n` is the very last
+        // byte read into one chunk, while the `\r` immediately before it is the very last byte of
+        // the chunk read after it (lower file offset, since this reader walks backward).
+        let prefix = b"first\n";
+        let mut mid = vec![b'Y'; REVERSE_READER_CHUNK_SIZE - 3];
+        mid.extend_from_slice(b"A\r\n");
+        let mut suffix = vec![b'S'; REVERSE_READER_CHUNK_SIZE - 1];
+        suffix.push(b'\n');
+        assert_eq!(mid.len(), REVERSE_READER_CHUNK_SIZE);
+        assert_eq!(suffix.len(), REVERSE_READER_CHUNK_SIZE);
+
+        let mut input = Vec::new();
+        input.extend_from_slice(prefix);
+        input.extend_from_slice(&mid);
+        input.extend_from_slice(&suffix);
+
+        let mut reader = ReverseLineReader::new(Cursor::new(input.clone()), 0, input.len() as u64)?
+            .with_strip_cr(true);
+
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line()? {
+            lines.push(line);
+        }
+
+        let mut expected_mid = vec![b'Y'; REVERSE_READER_CHUNK_SIZE - 3];
+        expected_mid.extend_from_slice(b"A\n");
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], suffix);
+        assert_eq!(lines[1], expected_mid);
+        assert_eq!(lines[2], b"first".to_vec());
+        Ok(())
+    }
+
+    /// A reader over a fixed byte slice that cancels `token` once `cancel_after` cumulative bytes
+    /// have been handed out across all `read` calls (regardless of how `seek` moves the read
+    /// position), simulating a cancellation signal arriving mid-scan without needing a second
+    /// thread. Lets tests assert a reader wired up with `with_cancellation_token` bails out well
+    /// before reaching the end of a large, delimiter-free input.
+    struct CancelAfterBytes {
+        data: Vec<u8>,
+        pos: usize,
+        bytes_served: usize,
+        token: CancellationToken,
+        cancel_after: usize,
+    }
+
+    impl Read for CancelAfterBytes {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pos >= self.data.len() {
+                return Ok(0);
+            }
+            let take = std::cmp::min(buf.len(), self.data.len() - self.pos);
+            buf[..take].copy_from_slice(&self.data[self.pos..self.pos + take]);
+            self.pos += take;
+            self.bytes_served += take;
+            if self.bytes_served >= self.cancel_after {
+                self.token.cancel();
+            }
+            Ok(take)
+        }
+    }
+
+    impl Seek for CancelAfterBytes {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            let new_pos = match pos {
+                SeekFrom::Start(offset) => offset as i64,
+                SeekFrom::End(offset) => self.data.len() as i64 + offset,
+                SeekFrom::Current(offset) => self.pos as i64 + offset,
+            };
+            self.pos = new_pos.max(0) as usize;
+            Ok(self.pos as u64)
+        }
+    }
+
+    #[test]
+    fn log_trimmer_reader_stops_promptly_on_cancellation_mid_scan() -> Result<(), Box<dyn Error>> {
+        // 8 MB of delimiter-free bytes: if cancellation weren't checked inside
+        // `refill_buffer_cri`'s inner loop, this would have to be scanned in full before giving up.
+        let data = vec![b'a'; 8 * 1024 * 1024];
+        let token = CancellationToken::new();
+        let source = CancelAfterBytes {
+            data,
+            pos: 0,
+            bytes_served: 0,
+            token: token.clone(),
+            cancel_after: 3 * LOG_TRIMMER_READER_BUFFER_SIZE,
+        };
+
+        let mut reader =
+            LogTrimmerReader::new(source, FileFormat::CRI, 0).with_cancellation_token(token);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+
+        assert!(buf.is_empty(), "cancellation should cut the scan off before any line completes");
+        assert!(
+            reader.get_inner_mut().bytes_served < 1024 * 1024,
+            "should stop well short of scanning the full 8 MB input"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_line_reader_stops_promptly_on_cancellation_mid_scan() -> Result<(), Box<dyn Error>> {
+        // Same idea in reverse: an 8 MB delimiter-free input would otherwise require walking
+        // every `REVERSE_READER_CHUNK_SIZE` chunk back to the start before giving up.
+        let data = vec![b'a'; 8 * 1024 * 1024];
+        let len = data.len() as u64;
+        let token = CancellationToken::new();
+        let source = CancelAfterBytes {
+            data,
+            pos: 0,
+            bytes_served: 0,
+            token: token.clone(),
+            cancel_after: 3 * REVERSE_READER_CHUNK_SIZE,
+        };
+
+        let mut reader = ReverseLineReader::new(source, 0, len)?.with_cancellation_token(token);
+        let line = reader.next_line()?;
+
+        assert_eq!(line, None, "cancellation should surface as an EOF-like None, not a full scan");
+        Ok(())
+    }
 }
@@ -12,19 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::str::FromStr;
 
 use prost_types::Timestamp;
 use serde_json;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{self, Sender, UnboundedSender};
 use tokio::task;
 use tokio_util::sync::CancellationToken;
 use tonic::Status;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use types::cluster_agent::LogRecord;
 
+use crate::util::compression::{self, Compression};
 use crate::util::format::FileFormat;
 use crate::util::reader::{TRUNCATION_HEX_LEN, TRUNCATION_SENTINEL};
 
@@ -83,15 +85,160 @@ where
     }
 }
 
-/// Function that processes the output.
-pub fn process_output(
+/// Spawns the task that turns raw ripgrep match lines into `LogRecord`s and forwards them to
+/// `sender`, and returns the sender used to feed it lines.
+///
+/// `CallbackWriter`'s callback runs synchronously inside the grep searcher's call stack, so it
+/// can't await the send itself. Routing lines through this unbounded channel keeps that callback
+/// non-blocking while the spawned task does the real, backpressured `.await`ed send -- no worker
+/// thread is parked waiting on a slow or full `sender` the way `block_in_place` would park one.
+pub fn spawn_line_processor(
     ctx: CancellationToken,
+    sender: Sender<Result<LogRecord, Status>>,
+    format: FileFormat,
+    policy: MalformedLinePolicy,
+    compression: Compression,
+) -> UnboundedSender<Vec<u8>> {
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+
+    task::spawn(async move {
+        let mut reassembler = CriLineReassembler::default();
+        while let Some(chunk) = line_rx.recv().await {
+            process_output(
+                &ctx,
+                chunk,
+                &sender,
+                format,
+                &mut reassembler,
+                &policy,
+                compression,
+            )
+            .await;
+        }
+    });
+
+    line_tx
+}
+
+/// What to do when a ripgrep frame or its embedded timestamp can't be parsed, instead of the
+/// unwinding that `.unwrap()`-ing those parses used to cause.
+#[derive(Debug, Clone)]
+pub enum MalformedLinePolicy {
+    /// Drop the line and log a tracing warning; the stream continues as if it never arrived.
+    Skip,
+    /// Drop the line but send `Status` through the channel so the client learns something was lost.
+    Emit(Status),
+    /// Send a `Status` built from the parse failure and cancel `ctx`, ending the stream.
+    Fail,
+}
+
+/// Applies `policy` to a line that failed to parse, as described by `reason`.
+async fn handle_malformed_line(
+    ctx: &CancellationToken,
+    sender: &Sender<Result<LogRecord, Status>>,
+    policy: &MalformedLinePolicy,
+    reason: impl std::fmt::Display,
+) {
+    match policy {
+        MalformedLinePolicy::Skip => {
+            warn!("skipping malformed line: {reason}");
+        }
+        MalformedLinePolicy::Emit(status) => {
+            warn!("surfacing status for malformed line: {reason}");
+            let _ = sender.send(Err(status.clone())).await;
+        }
+        MalformedLinePolicy::Fail => {
+            warn!("aborting stream on malformed line: {reason}");
+            let _ = sender
+                .send(Err(Status::data_loss(format!("malformed line: {reason}"))))
+                .await;
+            ctx.cancel();
+        }
+    }
+}
+
+/// Buffers CRI partial-line (`P`) fragments per stream (stdout/stderr) until the terminating
+/// `F` fragment arrives, so a kubelet-split log line is reassembled into a single `LogRecord`
+/// instead of being emitted once per fragment with a duplicated-looking timestamp.
+#[derive(Default)]
+struct CriLineReassembler {
+    pending: HashMap<String, (String, String)>,
+}
+
+impl CriLineReassembler {
+    /// Feeds one parsed CRI fragment and returns the reassembled `(timestamp, message)` once
+    /// `tag` is anything other than `"P"` (i.e. `"F"`); while `tag` is `"P"` it buffers `fragment`
+    /// under `stream` and returns `None`.
+    fn push(
+        &mut self,
+        stream: &str,
+        tag: &str,
+        timestamp: &str,
+        fragment: &str,
+    ) -> Option<(String, String)> {
+        let message = match self.pending.remove(stream) {
+            Some((_, buffered)) => buffered + fragment,
+            None => fragment.to_string(),
+        };
+
+        if tag == "P" {
+            self.pending
+                .insert(stream.to_string(), (timestamp.to_string(), message));
+            None
+        } else {
+            Some((timestamp.to_string(), message))
+        }
+    }
+}
+
+/// Builds the `LogRecord` for a parsed line, compressing `message` into `compressed_message` and
+/// clearing `message` when `compression` isn't `Compression::None` so a caller that asked for
+/// compression only ever sees one representation of the text on the wire.
+fn build_record(
+    timestamp: Option<Timestamp>,
+    message: String,
+    original_size_bytes: u64,
+    is_truncated: bool,
+    compression: Compression,
+) -> LogRecord {
+    let (message, compressed_message) = match compression {
+        Compression::None => (message, Vec::new()),
+        codec => (
+            String::new(),
+            compression::encode(codec, message.as_bytes()).unwrap_or_default(),
+        ),
+    };
+
+    LogRecord {
+        timestamp,
+        message,
+        original_size_bytes,
+        is_truncated,
+        compressed_message,
+        // Only a multi-file stream like `stream_forward_dir` knows which file a record came
+        // from; it tags this field itself after receiving the record from this single-file path.
+        source_path: String::new(),
+    }
+}
+
+/// Function that processes the output.
+pub async fn process_output(
+    ctx: &CancellationToken,
     chunk: Vec<u8>,
     sender: &Sender<Result<LogRecord, Status>>,
     format: FileFormat,
+    reassembler: &mut CriLineReassembler,
+    policy: &MalformedLinePolicy,
+    compression: Compression,
 ) {
-    // For example, convert to string and print.
-    let json: serde_json::Value = serde_json::from_slice(&chunk).unwrap();
+    let json: serde_json::Value = match serde_json::from_slice(&chunk) {
+        Ok(json) => json,
+        Err(error) => {
+            handle_malformed_line(ctx, sender, policy, format!("invalid ripgrep frame: {error}"))
+                .await;
+            return;
+        }
+    };
     if let (Some(t), Some(data)) = (json["type"].as_str(), json["data"].as_object()) {
         if t != "match" {
             return;
@@ -109,17 +256,112 @@ pub fn process_output(
                                 let (message, original_size_bytes, is_truncated) =
                                     normalize_message(log_msg);
 
-                                let record = LogRecord {
-                                    timestamp: Some(
-                                        Timestamp::from_str(time_str).unwrap_or_default(),
+                                let record = build_record(
+                                    Some(Timestamp::from_str(time_str).unwrap_or_default()),
+                                    message,
+                                    original_size_bytes,
+                                    is_truncated,
+                                    compression,
+                                );
+
+                                let result = sender.send(Ok(record)).await;
+                                if result.is_err() {
+                                    debug!("Channel closed from client.");
+                                    ctx.cancel();
+                                }
+                            }
+                        }
+                    }
+                    FileFormat::JsonLines => {
+                        if let Ok(log_json) = serde_json::from_str::<serde_json::Value>(text) {
+                            let timestamp = log_json
+                                .get("timestamp")
+                                .or_else(|| log_json.get("time"))
+                                .and_then(|t| t.as_str());
+                            let log_msg = log_json
+                                .get("message")
+                                .or_else(|| log_json.get("msg"))
+                                .and_then(|m| m.as_str());
+
+                            if let (Some(time_str), Some(log_msg)) = (timestamp, log_msg) {
+                                let (message, original_size_bytes, is_truncated) =
+                                    normalize_message(log_msg);
+
+                                let record = build_record(
+                                    Some(Timestamp::from_str(time_str).unwrap_or_default()),
+                                    message,
+                                    original_size_bytes,
+                                    is_truncated,
+                                    compression,
+                                );
+
+                                let result = sender.send(Ok(record)).await;
+                                if result.is_err() {
+                                    debug!("Channel closed from client.");
+                                    ctx.cancel();
+                                }
+                            }
+                        }
+                    }
+                    FileFormat::Klog => {
+                        if let Some((header, message)) = text.split_once("] ") {
+                            // header looks like "I0731 12:00:00.123456       1 main.go:10"
+                            if header.len() >= 21 {
+                                let klog_timestamp = &header[1..21];
+                                let year = chrono::Utc::now().format("%Y").to_string();
+                                let parsed = chrono::NaiveDateTime::parse_from_str(
+                                    &format!("{year}{klog_timestamp}"),
+                                    "%Y%m%d %H:%M:%S%.6f",
+                                );
+
+                                if let Ok(naive) = parsed {
+                                    let (message, original_size_bytes, is_truncated) =
+                                        normalize_message(message.trim_end_matches(['\n', '\r']));
+
+                                    let record = build_record(
+                                        Some(Timestamp::from_str(&naive.and_utc().to_rfc3339())
+                                            .unwrap_or_default()),
+                                        message,
+                                        original_size_bytes,
+                                        is_truncated,
+                                        compression,
+                                    );
+
+                                    let result = sender.send(Ok(record)).await;
+                                    if result.is_err() {
+                                        debug!("Channel closed from client.");
+                                        ctx.cancel();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    FileFormat::Syslog => {
+                        // Classic BSD/RFC 3164 syslog (`Mon DD HH:MM:SS host tag: message`). The
+                        // header carries no year, so (unlike the other arms here, which parse
+                        // their timestamp inline) this defers to `FileFormat::parse_timestamp`
+                        // for the current-year fallback it already implements for this format.
+                        if let Some(log_msg) = text.find(": ").map(|idx| &text[idx + 2..]) {
+                            if let Ok(parsed) = format.parse_timestamp(
+                                text.trim_end_matches(['\n', '\r']),
+                                None,
+                                true,
+                            ) {
+                                let (message, original_size_bytes, is_truncated) =
+                                    normalize_message(log_msg);
+
+                                let record = build_record(
+                                    Some(
+                                        Timestamp::from_str(&parsed.to_rfc3339())
+                                            .unwrap_or_default(),
                                     ),
                                     message,
                                     original_size_bytes,
                                     is_truncated,
-                                };
+                                    compression,
+                                );
 
-                                let result =
-                                    task::block_in_place(|| sender.blocking_send(Ok(record)));
+                                let result = sender.send(Ok(record)).await;
                                 if result.is_err() {
                                     debug!("Channel closed from client.");
                                     ctx.cancel();
@@ -127,6 +369,20 @@ pub fn process_output(
                             }
                         }
                     }
+                    FileFormat::JournaldExport => {
+                        // journald-export's record spans a blank-line-terminated block of
+                        // `KEY=value` lines, not a single line the way every other branch here
+                        // assumes (see `FileFormat::JournaldExport`'s doc comment). Until there's
+                        // a block-reassembling reader analogous to `CriLineReassembler`, this
+                        // format can't be streamed through this per-line path.
+                        handle_malformed_line(
+                            ctx,
+                            sender,
+                            policy,
+                            "journald-export record assembly is not yet supported",
+                        )
+                        .await;
+                    }
                     FileFormat::CRI => {
                         // Original logic for CRI format
                         if let Some((first, rest)) = text.split_once(' ') {
@@ -135,20 +391,45 @@ pub fn process_output(
                                 return;
                             }
 
-                            let (message, original_size_bytes, is_truncated) =
-                                normalize_message(&rest[9..]);
+                            let stream = &rest[0..6];
+                            let tag = &rest[7..8];
+                            let fragment = rest[9..].trim_end_matches(['\n', '\r']);
+
+                            if let Some((timestamp, raw_message)) =
+                                reassembler.push(stream, tag, first, fragment)
+                            {
+                                let parsed_timestamp = match Timestamp::from_str(&timestamp) {
+                                    Ok(timestamp) => timestamp,
+                                    Err(error) => {
+                                        handle_malformed_line(
+                                            ctx,
+                                            sender,
+                                            policy,
+                                            format!(
+                                                "invalid CRI timestamp {timestamp:?}: {error}"
+                                            ),
+                                        )
+                                        .await;
+                                        return;
+                                    }
+                                };
 
-                            let record = LogRecord {
-                                timestamp: Some(Timestamp::from_str(first).unwrap()),
-                                message,
-                                original_size_bytes,
-                                is_truncated,
-                            };
+                                let (message, original_size_bytes, is_truncated) =
+                                    normalize_message(&raw_message);
 
-                            let result = task::block_in_place(|| sender.blocking_send(Ok(record)));
-                            if result.is_err() {
-                                debug!("Channel closed from client.");
-                                ctx.cancel();
+                                let record = build_record(
+                                    Some(parsed_timestamp),
+                                    message,
+                                    original_size_bytes,
+                                    is_truncated,
+                                    compression,
+                                );
+
+                                let result = sender.send(Ok(record)).await;
+                                if result.is_err() {
+                                    debug!("Channel closed from client.");
+                                    ctx.cancel();
+                                }
                             }
                         }
                     }
@@ -190,9 +471,14 @@ fn normalize_message(raw: &str) -> (String, u64, bool) {
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_message, process_output};
+    use super::{
+        normalize_message, process_output, spawn_line_processor, CriLineReassembler,
+        MalformedLinePolicy,
+    };
+    use crate::util::compression::{self, Compression};
     use crate::util::{format::FileFormat, reader::LogTrimmerReader};
     use std::io::{Cursor, Read};
+    use std::str::FromStr;
     use tokio::sync::mpsc;
     use tokio_util::sync::CancellationToken;
 
@@ -249,11 +535,148 @@ mod tests {
 
         let (tx, mut rx) = mpsc::channel(1);
         let ctx = CancellationToken::new();
-        process_output(ctx, chunk_bytes, &tx, FileFormat::CRI);
+        let mut reassembler = CriLineReassembler::default();
+        process_output(
+            &ctx,
+            chunk_bytes,
+            &tx,
+            FileFormat::CRI,
+            &mut reassembler,
+            &MalformedLinePolicy::Skip,
+            Compression::None,
+        )
+        .await;
 
         let record = rx.recv().await.unwrap().unwrap();
         assert_eq!(record.message.len(), LIMIT as usize);
         assert_eq!(record.original_size_bytes, message.len() as u64);
         assert!(record.is_truncated);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn spawn_line_processor_forwards_lines_without_blocking_the_caller() {
+        let line = "2024-11-20T10:00:00Z stdout F hello\n";
+        let chunk = serde_json::json!({
+            "type": "match",
+            "data": { "lines": { "text": line } }
+        });
+        let chunk_bytes = serde_json::to_vec(&chunk).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let ctx = CancellationToken::new();
+        let line_tx = spawn_line_processor(
+            ctx,
+            tx,
+            FileFormat::CRI,
+            MalformedLinePolicy::Skip,
+            Compression::None,
+        );
+
+        // A synchronous, non-blocking send from the (simulated) grep sink callback.
+        line_tx.send(chunk_bytes).unwrap();
+
+        let record = rx.recv().await.unwrap().unwrap();
+        assert_eq!(record.message, "hello");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn spawn_line_processor_compresses_message_when_requested() {
+        let line = "2024-11-20T10:00:00Z stdout F hello\n";
+        let chunk = serde_json::json!({
+            "type": "match",
+            "data": { "lines": { "text": line } }
+        });
+        let chunk_bytes = serde_json::to_vec(&chunk).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let ctx = CancellationToken::new();
+        let line_tx = spawn_line_processor(
+            ctx,
+            tx,
+            FileFormat::CRI,
+            MalformedLinePolicy::Skip,
+            Compression::Zstd,
+        );
+
+        line_tx.send(chunk_bytes).unwrap();
+
+        let record = rx.recv().await.unwrap().unwrap();
+        assert!(record.message.is_empty());
+        assert_eq!(
+            compression::decode(Compression::Zstd, &record.compressed_message).unwrap(),
+            b"hello"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn spawn_line_processor_reassembles_partial_cri_fragments() {
+        let chunk_for = |line: &str| {
+            let chunk = serde_json::json!({
+                "type": "match",
+                "data": { "lines": { "text": line } }
+            });
+            serde_json::to_vec(&chunk).unwrap()
+        };
+
+        let (tx, mut rx) = mpsc::channel(2);
+        let ctx = CancellationToken::new();
+        let line_tx = spawn_line_processor(
+            ctx,
+            tx,
+            FileFormat::CRI,
+            MalformedLinePolicy::Skip,
+            Compression::None,
+        );
+
+        line_tx
+            .send(chunk_for("2024-11-20T10:00:00Z stdout P hel\n"))
+            .unwrap();
+        line_tx
+            .send(chunk_for("2024-11-20T10:00:01Z stdout F lo\n"))
+            .unwrap();
+
+        let record = rx.recv().await.unwrap().unwrap();
+        assert_eq!(record.message, "hello");
+        assert_eq!(
+            record.timestamp.unwrap(),
+            prost_types::Timestamp::from_str("2024-11-20T10:00:01Z").unwrap()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn spawn_line_processor_keeps_separate_streams_independent() {
+        let chunk_for = |line: &str| {
+            let chunk = serde_json::json!({
+                "type": "match",
+                "data": { "lines": { "text": line } }
+            });
+            serde_json::to_vec(&chunk).unwrap()
+        };
+
+        let (tx, mut rx) = mpsc::channel(2);
+        let ctx = CancellationToken::new();
+        let line_tx = spawn_line_processor(
+            ctx,
+            tx,
+            FileFormat::CRI,
+            MalformedLinePolicy::Skip,
+            Compression::None,
+        );
+
+        line_tx
+            .send(chunk_for("2024-11-20T10:00:00Z stdout P out-\n"))
+            .unwrap();
+        line_tx
+            .send(chunk_for("2024-11-20T10:00:00Z stderr F err\n"))
+            .unwrap();
+        line_tx
+            .send(chunk_for("2024-11-20T10:00:01Z stdout F frag\n"))
+            .unwrap();
+
+        let first = rx.recv().await.unwrap().unwrap();
+        assert_eq!(first.message, "err");
+
+        let second = rx.recv().await.unwrap().unwrap();
+        assert_eq!(second.message, "out-frag");
+    }
 }